@@ -0,0 +1,159 @@
+//! Static files referenced by the rendered resume.
+//!
+//! Templates refer to stylesheets and icons through the `asset` filter, which records every
+//! path it sees, so we know exactly which files have to be shipped alongside the HTML. When
+//! inlining is enabled, the filters embed the files into the HTML instead.
+//!
+//! Files are looked up in the registered search directories first, then among the files added
+//! one by one, and finally among the ones built into briefly.
+use ::std::borrow::Cow;
+use ::std::collections::{BTreeSet, HashMap};
+use ::std::path::{Component, Path, PathBuf};
+use ::std::sync::atomic::{AtomicBool, Ordering};
+use ::std::sync::Mutex;
+use anyhow::Error;
+
+const BUILTIN: &[(&str, &[u8])] = &[
+	("style.css", include_bytes!("../style.css")),
+	("print_style.css", include_bytes!("../print_style.css")),
+	("icons/blog.svg", include_bytes!("../icons/blog.svg")),
+	(
+		"icons/certificate-diploma.svg",
+		include_bytes!("../icons/certificate-diploma.svg"),
+	),
+	("icons/fork.svg", include_bytes!("../icons/fork.svg")),
+	("icons/github.svg", include_bytes!("../icons/github.svg")),
+	("icons/gitlab.svg", include_bytes!("../icons/gitlab.svg")),
+	("icons/key.svg", include_bytes!("../icons/key.svg")),
+	("icons/link.svg", include_bytes!("../icons/link.svg")),
+	(
+		"icons/linkedin.svg",
+		include_bytes!("../icons/linkedin.svg"),
+	),
+	(
+		"icons/location.svg",
+		include_bytes!("../icons/location.svg"),
+	),
+	("icons/mail.svg", include_bytes!("../icons/mail.svg")),
+	(
+		"icons/mastodon.svg",
+		include_bytes!("../icons/mastodon.svg"),
+	),
+	("icons/matrix.svg", include_bytes!("../icons/matrix.svg")),
+	("icons/orcid.svg", include_bytes!("../icons/orcid.svg")),
+	("icons/phone.svg", include_bytes!("../icons/phone.svg")),
+	("icons/scholar.svg", include_bytes!("../icons/scholar.svg")),
+	(
+		"icons/stackoverflow.svg",
+		include_bytes!("../icons/stackoverflow.svg"),
+	),
+	("icons/star.svg", include_bytes!("../icons/star.svg")),
+	("icons/twitter.svg", include_bytes!("../icons/twitter.svg")),
+];
+
+lazy_static::lazy_static! {
+	static ref USED: Mutex<BTreeSet<String>> = Mutex::new(BTreeSet::new());
+	static ref SEARCH_DIRS: Mutex<Vec<PathBuf>> = Mutex::new(Vec::new());
+	static ref FILES: Mutex<HashMap<String, PathBuf>> = Mutex::new(HashMap::new());
+}
+
+/// Look for assets in `dir` before falling back to the built-in ones.
+pub(crate) fn add_search_dir(dir: &Path) {
+	SEARCH_DIRS.lock().unwrap().push(dir.to_owned());
+}
+
+/// Read the asset `path` from `file`, unless it is in one of the search directories.
+pub(crate) fn add_file(path: &str, file: PathBuf) {
+	FILES.lock().unwrap().insert(path.to_owned(), file);
+}
+
+static INLINE: AtomicBool = AtomicBool::new(false);
+
+/// Embed assets into the output instead of linking to them.
+pub(crate) fn set_inline(inline: bool) {
+	INLINE.store(inline, Ordering::Relaxed);
+}
+
+pub(crate) fn is_inline() -> bool {
+	INLINE.load(Ordering::Relaxed)
+}
+
+/// Record that `path` is referenced by the output.
+pub(crate) fn record(path: &str) {
+	USED.lock().unwrap().insert(path.to_owned());
+}
+
+/// Returns the assets recorded so far, and forget about them.
+pub(crate) fn take_used() -> BTreeSet<String> {
+	::std::mem::take(&mut *USED.lock().unwrap())
+}
+
+pub(crate) fn get(path: &str) -> Option<Cow<'static, [u8]>> {
+	for dir in SEARCH_DIRS.lock().unwrap().iter() {
+		if let Ok(data) = std::fs::read(dir.join(path)) {
+			return Some(Cow::Owned(data));
+		}
+	}
+	if let Some(file) = FILES.lock().unwrap().get(path) {
+		if let Ok(data) = std::fs::read(file) {
+			return Some(Cow::Owned(data));
+		}
+	}
+	BUILTIN
+		.iter()
+		.find(|(p, _)| *p == path)
+		.map(|(_, d)| Cow::Borrowed(*d))
+}
+
+fn mime_type(path: &str) -> &'static str {
+	match Path::new(path).extension().and_then(|e| e.to_str()) {
+		Some("svg") => "image/svg+xml",
+		Some("css") => "text/css",
+		Some("png") => "image/png",
+		_ => "application/octet-stream",
+	}
+}
+
+pub(crate) fn data_uri(path: &str) -> Result<String, Error> {
+	use base64::Engine;
+	let data = get(path).ok_or_else(|| anyhow::anyhow!("Unknown asset {}", path))?;
+	Ok(format!(
+		"data:{};base64,{}",
+		mime_type(path),
+		base64::engine::general_purpose::STANDARD.encode(&data)
+	))
+}
+
+/// Write `html` as `index.html` into `dir`, together with every asset in `used`.
+pub(crate) fn write_dir(
+	dir: &Path,
+	html: &[u8],
+	used: &BTreeSet<String>,
+) -> Result<(), Error> {
+	// Check every asset first, so an error doesn't leave a partial directory behind
+	let mut assets = Vec::new();
+	for path in used {
+		let data = get(path).ok_or_else(|| anyhow::anyhow!("Unknown asset {}", path))?;
+		// Assets are referred to by relative URLs, they have to end up inside `dir`
+		if !Path::new(path)
+			.components()
+			.all(|c| matches!(c, Component::Normal(_) | Component::CurDir))
+		{
+			return Err(anyhow::anyhow!(
+				"Asset {} is not inside the output directory, use a relative path without \
+				 `..`",
+				path
+			));
+		}
+		assets.push((dir.join(path), data));
+	}
+	std::fs::create_dir_all(dir)?;
+	std::fs::write(dir.join("index.html"), html)?;
+	for (target, data) in assets {
+		if let Some(parent) = target.parent() {
+			std::fs::create_dir_all(parent)?;
+		}
+		std::fs::write(target, data)?;
+	}
+	Ok(())
+}
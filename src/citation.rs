@@ -24,21 +24,13 @@ impl UrlCitation {
 		let mut in_title = false;
 		for token in Tokenizer::new(&text).infallible() {
 			match token {
-				Token::StartTag(tag) if tag.name.as_slice() == b"title" => {
-					if title.is_none() {
-						in_title = true;
-					}
+				Token::StartTag(tag) if tag.name.as_slice() == b"title" && title.is_none() => {
+					in_title = true;
 				}
-				Token::String(s) => {
-					if in_title {
-						title_bytes.extend_from_slice(s.as_slice())
-					}
-				}
-				Token::EndTag(tag) if tag.name.as_slice() == b"title" => {
-					if title.is_none() {
-						title = Some(String::from_utf8_lossy(&title_bytes).to_string());
-						in_title = false;
-					}
+				Token::String(s) if in_title => title_bytes.extend_from_slice(s.as_slice()),
+				Token::EndTag(tag) if tag.name.as_slice() == b"title" && title.is_none() => {
+					title = Some(String::from_utf8_lossy(&title_bytes).to_string());
+					in_title = false;
 				}
 				_ => (),
 			}
@@ -69,11 +61,6 @@ impl UrlCitation {
 	}
 }
 
-#[derive(Deserialize, Serialize, Debug)]
-pub(crate) struct BibtexFileCitation {
-	bibtex: ::std::path::PathBuf,
-}
-
 #[derive(Deserialize, Serialize, Debug)]
 pub(crate) struct BibtexCitation {
 	bibtex_string: String,
@@ -89,7 +76,7 @@ impl BibtexCitation {
 			warn!("more than 1 citations found in bibtex, only use the first one");
 		}
 		let data = data
-			.get(0)
+			.first()
 			.map(Ok)
 			.unwrap_or(Err(anyhow::anyhow!("No citations found in bibtex")))?;
 		self.tags = Some(
@@ -212,8 +199,8 @@ impl DoiCitation {
 			if let Some(issued) = csl
 				.issued
 				.as_ref()
-				.and_then(|i| i.date_parts.get(0))
-				.and_then(|d| d.get(0))
+				.and_then(|i| i.date_parts.first())
+				.and_then(|d| d.first())
 			{
 				write!(&mut ret, "{}. ", issued).unwrap();
 			}
@@ -245,8 +232,32 @@ impl DoiCitation {
 		self.csl
 			.as_ref()
 			.and_then(|c| c.issued.as_ref())
-			.and_then(|c| c.date_parts.get(0))
-			.and_then(|c| c.get(0))
+			.and_then(|c| c.date_parts.first())
+			.and_then(|c| c.first())
 			.map(|c| *c as u32)
 	}
 }
+
+/// Flatten the HTML produced by `to_raw` into plain text.
+pub(crate) fn plain_text(html: &str) -> String {
+	let mut ret = String::new();
+	for token in Tokenizer::new(html).infallible() {
+		if let Token::String(s) = token {
+			ret.push_str(&String::from_utf8_lossy(&s));
+		}
+	}
+	ret
+}
+
+/// The target of the first link in the HTML produced by `to_raw`.
+pub(crate) fn first_link(html: &str) -> Option<String> {
+	Tokenizer::new(html)
+		.infallible()
+		.find_map(|token| match token {
+			Token::StartTag(tag) if tag.name.as_slice() == b"a" => tag
+				.attributes
+				.get(b"href".as_slice())
+				.map(|v| String::from_utf8_lossy(v).into_owned()),
+			_ => None,
+		})
+}
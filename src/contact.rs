@@ -0,0 +1,229 @@
+//! The kinds of contacts we know how to link to, and the icons to show for them.
+//!
+//! Besides the built-in kinds, a resume can define its own in `contact_kinds`, which take
+//! precedence over the built-in ones.
+use crate::{Contact, ContactKind, ContactParams};
+use ::std::collections::HashMap;
+
+struct Builtin {
+	name: &'static str,
+	/// Other names this kind can be referred to by
+	aliases: &'static [&'static str],
+	/// Name of the service, for humans
+	title: &'static str,
+	icon: &'static str,
+	link: fn(&str) -> Option<String>,
+	/// Whether the link is a profile page at `https://host/{value}`, so it can be moved to a
+	/// self-hosted instance with `web_url`
+	web_profile: bool,
+}
+
+fn is_url(value: &str) -> bool {
+	value.starts_with("https://") || value.starts_with("http://")
+}
+
+/// Link to a profile at `base`, unless the value is already a URL.
+fn profile(base: &str, value: &str) -> Option<String> {
+	if is_url(value) {
+		Some(value.to_owned())
+	} else {
+		Some(format!("{}{}", base, value.trim_start_matches('@')))
+	}
+}
+
+/// `user@instance` to `https://instance/@user`
+fn mastodon(value: &str) -> Option<String> {
+	if is_url(value) {
+		return Some(value.to_owned());
+	}
+	let (user, instance) = value.trim_start_matches('@').split_once('@')?;
+	Some(format!("https://{}/@{}", instance, user))
+}
+
+/// `@user:server` and `#room:server` to a matrix.to link
+fn matrix(value: &str) -> Option<String> {
+	if (value.starts_with('@') || value.starts_with('#')) && value.contains(':') {
+		Some(format!("https://matrix.to/#/{}", value))
+	} else {
+		None
+	}
+}
+
+fn phone(value: &str) -> Option<String> {
+	let number: String = value
+		.chars()
+		.filter(|c| c.is_ascii_digit() || *c == '+')
+		.collect();
+	Some(format!("tel:{}", number))
+}
+
+/// Look up a key fingerprint on keys.openpgp.org
+fn pgp(value: &str) -> Option<String> {
+	let fingerprint: String = value.chars().filter(|c| !c.is_whitespace()).collect();
+	Some(format!("https://keys.openpgp.org/search?q={}", fingerprint))
+}
+
+const BUILTIN: &[Builtin] = &[
+	Builtin {
+		name: "github",
+		aliases: &[],
+		title: "GitHub",
+		icon: "icons/github.svg",
+		link: |v| profile("https://github.com/", v),
+		web_profile: true,
+	},
+	Builtin {
+		name: "email",
+		aliases: &["mail"],
+		title: "Email",
+		icon: "icons/mail.svg",
+		link: |v| Some(format!("mailto:{}", v)),
+		web_profile: false,
+	},
+	Builtin {
+		name: "blog",
+		aliases: &["url", "website", "homepage"],
+		title: "Website",
+		icon: "icons/blog.svg",
+		link: |v| Some(v.to_owned()),
+		web_profile: false,
+	},
+	Builtin {
+		name: "phone",
+		aliases: &["tel"],
+		title: "Phone",
+		icon: "icons/phone.svg",
+		link: phone,
+		web_profile: false,
+	},
+	Builtin {
+		name: "linkedin",
+		aliases: &[],
+		title: "LinkedIn",
+		icon: "icons/linkedin.svg",
+		link: |v| profile("https://www.linkedin.com/in/", v),
+		web_profile: true,
+	},
+	Builtin {
+		name: "gitlab",
+		aliases: &[],
+		title: "GitLab",
+		icon: "icons/gitlab.svg",
+		link: |v| profile("https://gitlab.com/", v),
+		web_profile: true,
+	},
+	Builtin {
+		name: "mastodon",
+		aliases: &["fediverse"],
+		title: "Mastodon",
+		icon: "icons/mastodon.svg",
+		link: mastodon,
+		web_profile: false,
+	},
+	Builtin {
+		name: "orcid",
+		aliases: &[],
+		title: "ORCID",
+		icon: "icons/orcid.svg",
+		link: |v| profile("https://orcid.org/", v),
+		web_profile: true,
+	},
+	Builtin {
+		name: "google_scholar",
+		aliases: &["scholar"],
+		title: "Google Scholar",
+		icon: "icons/scholar.svg",
+		link: |v| profile("https://scholar.google.com/citations?user=", v),
+		web_profile: false,
+	},
+	Builtin {
+		name: "stackoverflow",
+		aliases: &["stack_overflow"],
+		title: "Stack Overflow",
+		icon: "icons/stackoverflow.svg",
+		link: |v| profile("https://stackoverflow.com/users/", v),
+		web_profile: true,
+	},
+	Builtin {
+		name: "twitter",
+		aliases: &["x"],
+		title: "X",
+		icon: "icons/twitter.svg",
+		link: |v| profile("https://x.com/", v),
+		web_profile: true,
+	},
+	Builtin {
+		name: "matrix",
+		aliases: &[],
+		title: "Matrix",
+		icon: "icons/matrix.svg",
+		link: matrix,
+		web_profile: false,
+	},
+	Builtin {
+		name: "pgp",
+		aliases: &["gpg", "openpgp"],
+		title: "OpenPGP",
+		icon: "icons/key.svg",
+		link: pgp,
+		web_profile: false,
+	},
+];
+
+fn builtin(type_: &str) -> Option<&'static Builtin> {
+	BUILTIN
+		.iter()
+		.find(|k| k.name == type_ || k.aliases.contains(&type_))
+}
+
+/// Name of the service a contact is on, for humans.
+pub(crate) fn title(type_: &str) -> Option<&'static str> {
+	builtin(type_).map(|k| k.title)
+}
+
+/// Looks up contact kinds, both the built-in ones and the ones defined in the resume.
+pub(crate) struct Registry<'a> {
+	custom: &'a HashMap<String, ContactKind>,
+}
+
+impl<'a> Registry<'a> {
+	pub(crate) fn new(custom: &'a HashMap<String, ContactKind>) -> Self {
+		Self { custom }
+	}
+
+	pub(crate) fn resolve(&self, contact: &Contact) -> ContactParams {
+		if let Some(kind) = self.custom.get(&contact.type_) {
+			return ContactParams {
+				type_: contact.type_.clone(),
+				value: contact.value.clone(),
+				icon: kind.icon.clone(),
+				link: kind
+					.url
+					.as_ref()
+					.map(|url| url.replace("{value}", &contact.value)),
+			};
+		}
+		match builtin(&contact.type_) {
+			Some(kind) => ContactParams {
+				type_: kind.name.to_owned(),
+				value: contact.value.clone(),
+				icon: Some(kind.icon.to_owned()),
+				link: match &contact.web_url {
+					// Self-hosted, e.g. GitHub Enterprise
+					Some(web_url) if kind.web_profile => Some(format!(
+						"{}/{}",
+						web_url.trim_end_matches('/'),
+						contact.value.trim_start_matches('@')
+					)),
+					_ => (kind.link)(&contact.value),
+				},
+			},
+			None => ContactParams {
+				type_: contact.type_.clone(),
+				value: contact.value.clone(),
+				icon: None,
+				link: is_url(&contact.value).then(|| contact.value.clone()),
+			},
+		}
+	}
+}
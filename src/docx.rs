@@ -0,0 +1,546 @@
+//! Word (Office Open XML) output.
+//!
+//! The document is written by hand, it only needs a small part of WordprocessingML: paragraphs
+//! with a few styles, hyperlinks, numbered and bulleted lists, and borderless tables to put the
+//! dates of an entry next to its title.
+use crate::md::rich::{self, Block, Footnotes, Inline, Style};
+use crate::{render, Project, ProjectRole, ResumeParams, Section};
+use ::std::fmt::Write as _;
+use ::std::io::Write as _;
+use anyhow::Error;
+
+/// Width of the text area of an A4 page with 2cm margins, in twentieths of a point
+const TEXT_WIDTH: u32 = 9638;
+const DATE_WIDTH: u32 = 2400;
+const INDENT: u32 = 360;
+const GRAY: &str = "666666";
+
+const CONTENT_TYPES: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<Types xmlns="http://schemas.openxmlformats.org/package/2006/content-types">
+<Default Extension="rels" ContentType="application/vnd.openxmlformats-package.relationships+xml"/>
+<Default Extension="xml" ContentType="application/xml"/>
+<Override PartName="/word/document.xml" ContentType="application/vnd.openxmlformats-officedocument.wordprocessingml.document.main+xml"/>
+<Override PartName="/word/styles.xml" ContentType="application/vnd.openxmlformats-officedocument.wordprocessingml.styles+xml"/>
+<Override PartName="/word/numbering.xml" ContentType="application/vnd.openxmlformats-officedocument.wordprocessingml.numbering+xml"/>
+<Override PartName="/docProps/core.xml" ContentType="application/vnd.openxmlformats-package.core-properties+xml"/>
+</Types>"#;
+
+const PACKAGE_RELS: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">
+<Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/officeDocument" Target="word/document.xml"/>
+<Relationship Id="rId2" Type="http://schemas.openxmlformats.org/package/2006/relationships/metadata/core-properties" Target="docProps/core.xml"/>
+</Relationships>"#;
+
+const STYLES: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<w:styles xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main">
+<w:docDefaults>
+<w:rPrDefault><w:rPr><w:rFonts w:ascii="Calibri" w:hAnsi="Calibri" w:eastAsia="Calibri" w:cs="Calibri"/><w:sz w:val="20"/><w:szCs w:val="20"/></w:rPr></w:rPrDefault>
+<w:pPrDefault><w:pPr><w:spacing w:after="60" w:line="259" w:lineRule="auto"/></w:pPr></w:pPrDefault>
+</w:docDefaults>
+<w:style w:type="paragraph" w:default="1" w:styleId="Normal"><w:name w:val="Normal"/></w:style>
+<w:style w:type="paragraph" w:styleId="Title"><w:name w:val="Title"/><w:basedOn w:val="Normal"/><w:next w:val="Normal"/><w:pPr><w:spacing w:after="80"/></w:pPr><w:rPr><w:b/><w:sz w:val="44"/><w:szCs w:val="44"/></w:rPr></w:style>
+<w:style w:type="paragraph" w:styleId="Heading1"><w:name w:val="heading 1"/><w:basedOn w:val="Normal"/><w:next w:val="Normal"/><w:pPr><w:keepNext/><w:keepLines/><w:pBdr><w:bottom w:val="single" w:sz="4" w:space="1" w:color="999999"/></w:pBdr><w:spacing w:before="240" w:after="120"/><w:outlineLvl w:val="0"/></w:pPr><w:rPr><w:b/><w:sz w:val="26"/><w:szCs w:val="26"/></w:rPr></w:style>
+<w:style w:type="paragraph" w:styleId="Heading2"><w:name w:val="heading 2"/><w:basedOn w:val="Normal"/><w:next w:val="Normal"/><w:pPr><w:keepNext/><w:keepLines/><w:spacing w:before="160" w:after="80"/><w:outlineLvl w:val="1"/></w:pPr><w:rPr><w:b/><w:sz w:val="22"/><w:szCs w:val="22"/></w:rPr></w:style>
+<w:style w:type="character" w:styleId="Hyperlink"><w:name w:val="Hyperlink"/><w:rPr><w:color w:val="0563C1"/><w:u w:val="single"/></w:rPr></w:style>
+</w:styles>"#;
+
+/// Escape text for use in XML.
+fn escape(s: &str) -> String {
+	let mut ret = String::with_capacity(s.len());
+	for c in s.chars() {
+		match c {
+			'&' => ret.push_str("&amp;"),
+			'<' => ret.push_str("&lt;"),
+			'>' => ret.push_str("&gt;"),
+			'"' => ret.push_str("&quot;"),
+			_ => ret.push(c),
+		}
+	}
+	ret
+}
+
+/// Formatting of a run of text.
+#[derive(Default, Clone, Copy)]
+struct Run {
+	style: Style,
+	gray: bool,
+	small: bool,
+	superscript: bool,
+}
+
+impl Run {
+	fn bold() -> Self {
+		Self {
+			style: Style {
+				bold: true,
+				..Default::default()
+			},
+			..Default::default()
+		}
+	}
+	fn italic() -> Self {
+		Self {
+			style: Style {
+				italic: true,
+				..Default::default()
+			},
+			..Default::default()
+		}
+	}
+	fn gray() -> Self {
+		Self {
+			gray: true,
+			small: true,
+			..Default::default()
+		}
+	}
+	fn xml(self, text: &str, link: bool) -> String {
+		let mut props = String::new();
+		if link {
+			props.push_str(r#"<w:rStyle w:val="Hyperlink"/>"#);
+		}
+		if self.style.code {
+			props.push_str(
+				r#"<w:rFonts w:ascii="Consolas" w:hAnsi="Consolas" w:cs="Consolas"/>"#,
+			);
+		}
+		if self.style.bold {
+			props.push_str("<w:b/>");
+		}
+		if self.style.italic {
+			props.push_str("<w:i/>");
+		}
+		if self.gray {
+			write!(props, r#"<w:color w:val="{}"/>"#, GRAY).unwrap();
+		}
+		if self.small {
+			props.push_str(r#"<w:sz w:val="17"/><w:szCs w:val="17"/>"#);
+		}
+		if self.superscript {
+			props.push_str(r#"<w:vertAlign w:val="superscript"/>"#);
+		}
+		format!(
+			r#"<w:r><w:rPr>{}</w:rPr><w:t xml:space="preserve">{}</w:t></w:r>"#,
+			props,
+			escape(text)
+		)
+	}
+}
+
+#[derive(Default)]
+struct Writer {
+	body: String,
+	/// Targets of the hyperlinks, their relationship ids are `rIdLink{index}`
+	links: Vec<String>,
+	/// The first number of each ordered list, their numbering ids start from 2
+	ordered_lists: Vec<u64>,
+	footnotes: Footnotes,
+}
+
+impl Writer {
+	fn link(&mut self, target: &str, runs: &str) -> String {
+		self.links.push(target.to_owned());
+		format!(
+			r#"<w:hyperlink r:id="rIdLink{}">{}</w:hyperlink>"#,
+			self.links.len() - 1,
+			runs
+		)
+	}
+	fn inlines(&mut self, inlines: &[Inline], base: Run) -> String {
+		let mut ret = String::new();
+		for inline in inlines {
+			match inline {
+				Inline::Text { text, style, link } => {
+					let run = Run {
+						style: Style {
+							bold: style.bold || base.style.bold,
+							italic: style.italic || base.style.italic,
+							code: style.code,
+						},
+						..base
+					};
+					match link {
+						Some(link) => {
+							let runs = run.xml(text, true);
+							ret.push_str(&self.link(link, &runs));
+						}
+						None => ret.push_str(&run.xml(text, false)),
+					}
+				}
+				Inline::FootnoteReference(name) => {
+					let n = self.footnotes.number(name);
+					let run = Run {
+						superscript: true,
+						..base
+					};
+					ret.push_str(&run.xml(&n.to_string(), false));
+				}
+				Inline::LineBreak => ret.push_str("<w:r><w:br/></w:r>"),
+			}
+		}
+		ret
+	}
+	fn text(&mut self, text: &str, run: Run, link: Option<&str>) -> String {
+		match link {
+			Some(link) => {
+				let runs = run.xml(text, true);
+				self.link(link, &runs)
+			}
+			None => run.xml(text, false),
+		}
+	}
+	fn paragraph(&mut self, props: &str, runs: &str) {
+		write!(self.body, "<w:p><w:pPr>{}</w:pPr>{}</w:p>", props, runs).unwrap();
+	}
+	fn heading(&mut self, level: u8, text: &str) {
+		let runs = Run::default().xml(text, false);
+		self.paragraph(&format!(r#"<w:pStyle w:val="Heading{}"/>"#, level), &runs);
+	}
+	fn blocks(&mut self, blocks: &[Block], level: u32) {
+		let indent = if level > 0 {
+			format!(r#"<w:ind w:left="{}"/>"#, level * INDENT)
+		} else {
+			String::new()
+		};
+		for block in blocks {
+			match block {
+				Block::Paragraph(inlines) => {
+					let runs = self.inlines(inlines, Run::default());
+					self.paragraph(&indent, &runs);
+				}
+				Block::Heading(inlines) => {
+					let runs = self.inlines(inlines, Run::bold());
+					self.paragraph(&format!("<w:keepNext/>{}", indent), &runs);
+				}
+				Block::List { start, items } => {
+					let num_id = match start {
+						Some(start) => {
+							self.ordered_lists.push(*start);
+							self.ordered_lists.len() + 1
+						}
+						None => 1,
+					};
+					for item in items {
+						let numbering = format!(
+							r#"<w:numPr><w:ilvl w:val="{}"/><w:numId w:val="{}"/></w:numPr>"#,
+							level.min(8),
+							num_id
+						);
+						// The first paragraph of an item gets the bullet or number
+						let rest = match item.split_first() {
+							Some((Block::Paragraph(inlines), rest)) => {
+								let runs = self.inlines(inlines, Run::default());
+								self.paragraph(&numbering, &runs);
+								rest
+							}
+							_ => {
+								self.paragraph(&numbering, "");
+								&item[..]
+							}
+						};
+						self.blocks(rest, level + 1);
+					}
+				}
+				Block::Quote(blocks) => self.blocks(blocks, level + 1),
+				Block::Code(code) => {
+					let run = Run {
+						style: Style {
+							code: true,
+							..Default::default()
+						},
+						..Default::default()
+					};
+					let runs: Vec<_> = code.lines().map(|l| run.xml(l, false)).collect();
+					self.paragraph(&indent, &runs.join("<w:r><w:br/></w:r>"));
+				}
+				Block::Rule => self.paragraph(
+					r#"<w:pBdr><w:bottom w:val="single" w:sz="4" w:space="1" w:color="999999"/></w:pBdr>"#,
+					"",
+				),
+			}
+		}
+	}
+	/// A borderless table with the title and subtitle of an entry on the left, and its dates
+	/// on the right.
+	fn entry(&mut self, title: &str, subtitle: &str, date: &str) {
+		let left = TEXT_WIDTH - DATE_WIDTH;
+		write!(
+			self.body,
+			concat!(
+				r#"<w:tbl><w:tblPr><w:tblW w:w="{tw}" w:type="dxa"/><w:tblLayout w:type="fixed"/>"#,
+				r#"<w:tblCellMar><w:left w:w="0" w:type="dxa"/><w:right w:w="0" w:type="dxa"/></w:tblCellMar>"#,
+				r#"</w:tblPr><w:tblGrid><w:gridCol w:w="{l}"/><w:gridCol w:w="{d}"/></w:tblGrid>"#,
+				r#"<w:tr><w:trPr><w:cantSplit/></w:trPr>"#,
+				r#"<w:tc><w:tcPr><w:tcW w:w="{l}" w:type="dxa"/></w:tcPr>"#,
+				r#"<w:p><w:pPr><w:keepNext/><w:spacing w:before="120" w:after="0"/></w:pPr>{title}</w:p>"#,
+				r#"<w:p><w:pPr><w:keepNext/></w:pPr>{subtitle}</w:p></w:tc>"#,
+				r#"<w:tc><w:tcPr><w:tcW w:w="{d}" w:type="dxa"/></w:tcPr>"#,
+				r#"<w:p><w:pPr><w:keepNext/><w:spacing w:before="120" w:after="0"/><w:jc w:val="right"/></w:pPr>{date}</w:p>"#,
+				r#"</w:tc></w:tr></w:tbl>"#
+			),
+			tw = TEXT_WIDTH,
+			l = left,
+			d = DATE_WIDTH,
+			title = title,
+			subtitle = subtitle,
+			date = Run::gray().xml(date, false),
+		)
+		.unwrap();
+	}
+	fn project(&mut self, p: &Project) {
+		let mut title = String::new();
+		if let Some(owner) = render::project_owner(p) {
+			title.push_str(&Run::gray().xml(&format!("{}/", owner), false));
+		}
+		let name = self.text(&p.name, Run::bold(), p.url.as_ref().map(|u| u.as_str()));
+		title.push_str(&name);
+		let stats = render::project_stats(p);
+		if p.role == Some(ProjectRole::Contributor) {
+			let contributions = p
+				.contributions
+				.as_deref()
+				.map(|c| self.inlines(&rich::parse_inline(c), Run::default()))
+				.unwrap_or_default();
+			self.entry(&title, &contributions, &stats.join(" · "));
+			return;
+		}
+		let languages: Vec<_> = p
+			.languages
+			.iter()
+			.take(4)
+			.map(|l| format!("{} {}%", l.language, l.percentage))
+			.collect();
+		let languages = Run::gray().xml(&languages.join(" · "), false);
+		self.entry(&title, &languages, &stats.join(" · "));
+		if let Some(description) = &p.description {
+			self.blocks(&rich::parse(description), 0);
+		}
+		if !p.tags.is_empty() {
+			let tags: Vec<_> = p.tags.iter().map(|t| format!("#{}", t)).collect();
+			let runs = Run::gray().xml(&tags.join(" "), false);
+			self.paragraph("", &runs);
+		}
+		if let Some(contributions) = &p.contributions {
+			let runs = Run::italic().xml("Contributions", false);
+			self.paragraph("<w:keepNext/>", &runs);
+			self.blocks(&rich::parse(contributions), 0);
+		}
+	}
+	fn section(&mut self, r: &ResumeParams<'_>, section: Section) {
+		match section {
+			Section::Education => {
+				self.heading(1, "Education");
+				for e in r.educations {
+					let mut subtitle = format!("{}, {}", e.degree.to_resume_string(), e.major);
+					if let Some(location) = &e.location {
+						write!(subtitle, " · {}", location).unwrap();
+					}
+					if let Some(gpa) = e.gpa {
+						write!(subtitle, " · GPA {}", gpa).unwrap();
+					}
+					self.entry(
+						&Run::bold().xml(&e.institution, false),
+						&Run::italic().xml(&subtitle, false),
+						&e.duration.to_text_string(),
+					);
+				}
+			}
+			Section::Work => {
+				self.heading(1, "Work");
+				for e in r.experiences {
+					let mut subtitle = e.position.clone();
+					if let Some(location) = &e.location {
+						write!(subtitle, " · {}", location).unwrap();
+					}
+					self.entry(
+						&Run::bold().xml(&e.company, false),
+						&Run::italic().xml(&subtitle, false),
+						&e.duration.to_text_string(),
+					);
+					self.blocks(&rich::parse(&e.description), 0);
+				}
+			}
+			Section::OpenSource => {
+				self.heading(1, "Open Source");
+				for (role, title) in render::PROJECT_SECTIONS {
+					if r.has_projects(role) {
+						self.heading(2, title);
+						for p in r.projects.iter().filter(|p| p.role == Some(role)) {
+							self.project(p);
+						}
+					}
+				}
+			}
+			Section::Skills => {
+				self.heading(1, "Skills");
+				for s in r.skills {
+					let mut runs = Run::bold().xml(&format!("{}: ", s.category), false);
+					if let Some(description) = &s.description {
+						runs.push_str(
+							&self.inlines(&rich::parse_inline(description), Run::default()),
+						);
+					}
+					self.paragraph("", &runs);
+				}
+			}
+			Section::Publications => {
+				self.heading(1, "Publications");
+				for (text, year) in &r.publications {
+					let mut runs = String::new();
+					if let Some(year) = year {
+						runs.push_str(&Run::bold().xml(&year.to_string(), false));
+						runs.push_str("<w:r><w:tab/></w:r>");
+					}
+					let citation = rich::emphasize(rich::from_html(text), r.name);
+					runs.push_str(&self.inlines(&citation, Run::default()));
+					self.paragraph(
+						&format!(r#"<w:ind w:left="{0}" w:hanging="{0}"/>"#, 2 * INDENT),
+						&runs,
+					);
+				}
+			}
+			Section::References => {
+				let references = self.footnotes.used(&r.references);
+				if references.is_empty() {
+					return;
+				}
+				self.heading(1, "References");
+				for (n, text) in references {
+					let mut runs = Run::default().xml(&format!("[{}] ", n), false);
+					runs.push_str(&self.inlines(&rich::from_html(text), Run::default()));
+					self.paragraph("", &runs);
+				}
+			}
+		}
+	}
+}
+
+fn numbering(ordered_lists: &[u64]) -> String {
+	let mut ret = String::from(concat!(
+		r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#,
+		"\n",
+		r#"<w:numbering xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main">"#
+	));
+	for (id, format) in [(0, "bullet"), (1, "decimal")] {
+		write!(ret, r#"<w:abstractNum w:abstractNumId="{}">"#, id).unwrap();
+		for level in 0..9 {
+			let text = match format {
+				"bullet" => ["•", "◦", "▪"][level as usize % 3].to_owned(),
+				_ => format!("%{}.", level + 1),
+			};
+			write!(
+				ret,
+				concat!(
+					r#"<w:lvl w:ilvl="{0}"><w:start w:val="1"/><w:numFmt w:val="{1}"/>"#,
+					r#"<w:lvlText w:val="{2}"/><w:lvlJc w:val="left"/>"#,
+					r#"<w:pPr><w:ind w:left="{3}" w:hanging="{4}"/></w:pPr></w:lvl>"#
+				),
+				level,
+				format,
+				text,
+				(level + 1) * INDENT,
+				INDENT
+			)
+			.unwrap();
+		}
+		ret.push_str("</w:abstractNum>");
+	}
+	ret.push_str(r#"<w:num w:numId="1"><w:abstractNumId w:val="0"/></w:num>"#);
+	for (i, start) in ordered_lists.iter().enumerate() {
+		write!(
+			ret,
+			concat!(
+				r#"<w:num w:numId="{}"><w:abstractNumId w:val="1"/>"#,
+				r#"<w:lvlOverride w:ilvl="0"><w:startOverride w:val="{}"/></w:lvlOverride></w:num>"#
+			),
+			i + 2,
+			start
+		)
+		.unwrap();
+	}
+	ret.push_str("</w:numbering>");
+	ret
+}
+
+fn document_rels(links: &[String]) -> String {
+	let mut ret = String::from(concat!(
+		r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#,
+		"\n",
+		r#"<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">"#,
+		r#"<Relationship Id="rIdStyles" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/styles" Target="styles.xml"/>"#,
+		r#"<Relationship Id="rIdNumbering" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/numbering" Target="numbering.xml"/>"#,
+	));
+	for (i, link) in links.iter().enumerate() {
+		write!(
+			ret,
+			r#"<Relationship Id="rIdLink{}" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/hyperlink" Target="{}" TargetMode="External"/>"#,
+			i,
+			escape(link)
+		)
+		.unwrap();
+	}
+	ret.push_str("</Relationships>");
+	ret
+}
+
+fn core_properties(name: &str) -> String {
+	format!(
+		concat!(
+			r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#,
+			"\n",
+			r#"<cp:coreProperties xmlns:cp="http://schemas.openxmlformats.org/package/2006/metadata/core-properties" "#,
+			r#"xmlns:dc="http://purl.org/dc/elements/1.1/">"#,
+			"<dc:title>{0}</dc:title><dc:creator>{0}</dc:creator></cp:coreProperties>"
+		),
+		escape(name)
+	)
+}
+
+/// Render the resume as a Word document.
+pub(crate) fn render(r: &ResumeParams<'_>) -> Result<Vec<u8>, Error> {
+	let mut w = Writer::default();
+	let name = Run::default().xml(r.name, false);
+	w.paragraph(r#"<w:pStyle w:val="Title"/>"#, &name);
+	let mut contacts = Vec::new();
+	for c in &r.contacts {
+		contacts.push(w.text(&c.value, Run::default(), c.link.as_deref()));
+	}
+	let separator = Run::gray().xml(" · ", false);
+	w.paragraph(r#"<w:spacing w:after="200"/>"#, &contacts.join(&separator));
+	let header = ::std::mem::take(&mut w.body);
+
+	let sections = render::sections(&r.sections, |s| {
+		w.section(r, s);
+		::std::mem::take(&mut w.body)
+	});
+	let document = format!(
+		concat!(
+			r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#,
+			"\n",
+			r#"<w:document xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main" "#,
+			r#"xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships">"#,
+			"<w:body>{}{}",
+			r#"<w:sectPr><w:pgSz w:w="11906" w:h="16838"/>"#,
+			r#"<w:pgMar w:top="1134" w:right="1134" w:bottom="1134" w:left="1134" w:header="567" w:footer="567" w:gutter="0"/>"#,
+			"</w:sectPr></w:body></w:document>"
+		),
+		header,
+		sections.concat()
+	);
+
+	let mut zip = ::zip::ZipWriter::new(::std::io::Cursor::new(Vec::new()));
+	let options = ::zip::write::FileOptions::default();
+	for (path, content) in [
+		("[Content_Types].xml", CONTENT_TYPES.to_owned()),
+		("_rels/.rels", PACKAGE_RELS.to_owned()),
+		("docProps/core.xml", core_properties(r.name)),
+		("word/document.xml", document),
+		("word/styles.xml", STYLES.to_owned()),
+		("word/numbering.xml", numbering(&w.ordered_lists)),
+		("word/_rels/document.xml.rels", document_rels(&w.links)),
+	] {
+		zip.start_file(path, options)?;
+		zip.write_all(content.as_bytes())?;
+	}
+	Ok(zip.finish()?.into_inner())
+}
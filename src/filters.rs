@@ -96,3 +96,48 @@ pub(crate) fn language_stats(languages: &[crate::LanguageStat]) -> ::askama::Res
 pub(crate) fn emph(s: &str, pat: &str) -> ::askama::Result<String> {
 	Ok(s.replace(pat, &format!("<u>{}</u>", pat)))
 }
+
+pub(crate) fn asset(path: &str) -> ::askama::Result<String> {
+	use crate::assets;
+	if assets::is_inline() {
+		return assets::data_uri(path).map_err(|e| ::askama::Error::Custom(e.into()));
+	}
+	assets::record(path);
+	Ok(path.to_owned())
+}
+
+pub(crate) fn stylesheet(path: &str, media: &str) -> ::askama::Result<String> {
+	use crate::assets;
+	if assets::is_inline() {
+		let css = assets::get(path).ok_or_else(|| {
+			::askama::Error::Custom(format!("Unknown asset {}", path).into())
+		})?;
+		// Imports would be fetched from the network, so the bundle leaves them out and falls
+		// back to the local fonts
+		let css: Vec<_> = String::from_utf8_lossy(&css)
+			.lines()
+			.filter(|l| !l.trim_start().starts_with("@import"))
+			.map(str::to_owned)
+			.collect();
+		return Ok(format!(
+			r#"<style media="{}">{}</style>"#,
+			media,
+			css.join("\n")
+		));
+	}
+	assets::record(path);
+	Ok(format!(
+		r#"<link href="{}" rel="stylesheet" media="{}" type="text/css">"#,
+		path, media
+	))
+}
+
+/// Embed `content` in a data URI, e.g. to offer it as a download.
+pub(crate) fn data_uri(content: &str, mime: &str) -> ::askama::Result<String> {
+	use base64::Engine;
+	Ok(format!(
+		"data:{};base64,{}",
+		mime,
+		base64::engine::general_purpose::STANDARD.encode(content)
+	))
+}
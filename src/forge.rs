@@ -0,0 +1,71 @@
+//! Helpers shared by the code forge importers.
+use crate::LanguageStat;
+use ::isahc::{prelude::*, Request};
+use ::log::*;
+use ::serde::de::DeserializeOwned;
+use ::std::collections::HashMap;
+use anyhow::Error;
+
+/// Turn the amount of code in each language into percentages, largest first.
+pub(crate) fn language_stats(sizes: HashMap<String, u64>) -> Vec<LanguageStat> {
+	let total: f64 = sizes.values().map(|v| *v as f64).sum();
+	let mut ret: Vec<_> = sizes
+		.into_iter()
+		.map(|(language, size)| LanguageStat {
+			language,
+			percentage: (size as f64 / total * 100.).into(),
+		})
+		.collect();
+	ret.sort_unstable_by_key(|v| ::std::cmp::Reverse(v.percentage));
+	ret
+}
+
+/// Escape a path, like `owner/repo`, so it can be used as one segment of a URL.
+pub(crate) fn path_segment(path: &str) -> String {
+	::url::form_urlencoded::byte_serialize(path.as_bytes()).collect()
+}
+
+/// GET `url` and parse the response as JSON.
+pub(crate) async fn get_json<T: DeserializeOwned>(
+	url: &str,
+	headers: &[(&str, String)],
+) -> Result<T, Error> {
+	let mut req = Request::builder()
+		.uri(url)
+		.header("User-Agent", "briefly/0.0")
+		.header("Accept", "application/json");
+	for (name, value) in headers {
+		req = req.header(*name, value);
+	}
+	debug!("GET {}", url);
+	let mut res = isahc::send_async(req.body(())?).await?;
+	let text = res.text().await?;
+	if !res.status().is_success() {
+		return Err(anyhow::anyhow!(
+			"GET {} failed with {}: {}",
+			url,
+			res.status(),
+			text
+		));
+	}
+	Ok(serde_json::from_str(&text)?)
+}
+
+/// GET all pages of a paginated list, by incrementing the `page` query parameter until an
+/// empty page is returned.
+pub(crate) async fn get_all_pages<T: DeserializeOwned>(
+	url: &str,
+	headers: &[(&str, String)],
+) -> Result<Vec<T>, Error> {
+	let separator = if url.contains('?') { '&' } else { '?' };
+	let mut ret = Vec::new();
+	for page in 1.. {
+		let items: Vec<T> =
+			get_json(&format!("{}{}page={}", url, separator, page), headers).await?;
+		if items.is_empty() {
+			break;
+		}
+		ret.extend(items);
+	}
+	Ok(ret)
+}
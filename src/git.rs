@@ -0,0 +1,183 @@
+//! Import of projects from local git repositories, without touching the network.
+use crate::{Project, ProjectRole};
+use ::git2::{ObjectType, Repository, TreeWalkMode, TreeWalkResult};
+use ::std::collections::HashMap;
+use ::std::path::Path;
+use anyhow::Error;
+
+/// Languages of source files, by file extension.
+const LANGUAGES: &[(&str, &str)] = &[
+	("c", "C"),
+	("h", "C"),
+	("cc", "C++"),
+	("cpp", "C++"),
+	("cxx", "C++"),
+	("hh", "C++"),
+	("hpp", "C++"),
+	("cs", "C#"),
+	("clj", "Clojure"),
+	("css", "CSS"),
+	("scss", "SCSS"),
+	("d", "D"),
+	("dart", "Dart"),
+	("ex", "Elixir"),
+	("exs", "Elixir"),
+	("elm", "Elm"),
+	("erl", "Erlang"),
+	("fs", "F#"),
+	("f90", "Fortran"),
+	("go", "Go"),
+	("hs", "Haskell"),
+	("html", "HTML"),
+	("java", "Java"),
+	("js", "JavaScript"),
+	("mjs", "JavaScript"),
+	("jsx", "JavaScript"),
+	("jl", "Julia"),
+	("kt", "Kotlin"),
+	("lua", "Lua"),
+	("m", "Objective-C"),
+	("ml", "OCaml"),
+	("mli", "OCaml"),
+	("nix", "Nix"),
+	("php", "PHP"),
+	("pl", "Perl"),
+	("py", "Python"),
+	("r", "R"),
+	("rb", "Ruby"),
+	("rs", "Rust"),
+	("scala", "Scala"),
+	("sh", "Shell"),
+	("bash", "Shell"),
+	("swift", "Swift"),
+	("tex", "TeX"),
+	("ts", "TypeScript"),
+	("tsx", "TypeScript"),
+	("vue", "Vue"),
+	("zig", "Zig"),
+];
+
+fn language(file_name: &str) -> Option<&'static str> {
+	let (_, extension) = file_name.rsplit_once('.')?;
+	let extension = extension.to_ascii_lowercase();
+	LANGUAGES
+		.iter()
+		.find(|(e, _)| *e == extension)
+		.map(|(_, language)| *language)
+}
+
+/// Turn the URL of a remote into the URL of its web page, if it looks like it has one.
+/// Credentials in the URL are left out, so they don't end up in the resume.
+fn web_url(remote: &str) -> Option<url::Url> {
+	let remote = remote.trim_end_matches('/').trim_end_matches(".git");
+	if remote.starts_with("https://") || remote.starts_with("http://") {
+		let mut url: url::Url = remote.parse().ok()?;
+		url.set_username("").ok()?;
+		url.set_password(None).ok()?;
+		return Some(url);
+	}
+	// scp-like syntax, `git@host:owner/repo`
+	let (host, path) = remote.split_once(':')?;
+	let host = host.rsplit('@').next()?;
+	if host.contains('/') || path.starts_with("//") {
+		return None;
+	}
+	format!("https://{}/{}", host, path).parse().ok()
+}
+
+/// Count the bytes of code in each language in the tree of `HEAD`.
+fn languages(repo: &Repository) -> Result<HashMap<String, u64>, Error> {
+	let tree = repo.head()?.peel_to_tree()?;
+	let odb = repo.odb()?;
+	let mut ret = HashMap::new();
+	let mut error = None;
+	tree.walk(TreeWalkMode::PreOrder, |_, entry| {
+		if entry.kind() != Some(ObjectType::Blob) {
+			return TreeWalkResult::Ok;
+		}
+		let Some(language) = entry.name().and_then(language) else {
+			return TreeWalkResult::Ok;
+		};
+		match odb.read_header(entry.id()) {
+			Ok((size, _)) => {
+				*ret.entry(language.to_owned()).or_default() += size as u64;
+				TreeWalkResult::Ok
+			}
+			Err(e) => {
+				error = Some(e);
+				TreeWalkResult::Abort
+			}
+		}
+	})?;
+	match error {
+		Some(e) => Err(e.into()),
+		None => Ok(ret),
+	}
+}
+
+/// Read the repository at `path`, counting the commits authored with one of `emails`. The
+/// project is considered owned if one of its root commits is one of those.
+pub(crate) fn get_project_from_git(path: &Path, emails: &[String]) -> Result<Project, Error> {
+	let repo = Repository::open(path)?;
+	let mailmap = repo.mailmap()?;
+	let is_author = |commit: &git2::Commit| -> Result<bool, Error> {
+		let author = commit.author_with_mailmap(&mailmap)?;
+		let email = author.email().unwrap_or_default();
+		Ok(emails.iter().any(|e| e.eq_ignore_ascii_case(email)))
+	};
+
+	let mut revwalk = repo.revwalk()?;
+	revwalk.push_head()?;
+	let (mut commits, mut additions, mut deletions) = (0, 0, 0);
+	let mut owned = false;
+	for id in revwalk {
+		let commit = repo.find_commit(id?)?;
+		if commit.parent_count() > 1 || !is_author(&commit)? {
+			continue;
+		}
+		let parent_tree = match commit.parent_count() {
+			0 => {
+				owned = true;
+				None
+			}
+			_ => Some(commit.parent(0)?.tree()?),
+		};
+		let diff =
+			repo.diff_tree_to_tree(parent_tree.as_ref(), Some(&commit.tree()?), None)?;
+		let stats = diff.stats()?;
+		commits += 1;
+		additions += stats.insertions() as u64;
+		deletions += stats.deletions() as u64;
+	}
+
+	let root = repo.workdir().unwrap_or_else(|| repo.path());
+	let name = root
+		.canonicalize()?
+		.file_name()
+		.map(|n| n.to_string_lossy().trim_end_matches(".git").to_owned())
+		.unwrap_or_default();
+	let url = repo
+		.find_remote("origin")
+		.ok()
+		.and_then(|r| r.url().and_then(web_url));
+	Ok(Project {
+		name,
+		description: None,
+		contributions: None,
+		url,
+		stars: None,
+		forks: None,
+		active: None,
+		owner: None,
+		commits: Some(commits),
+		additions: Some(additions),
+		deletions: Some(deletions),
+		languages: crate::forge::language_stats(languages(&repo)?),
+		tags: Vec::new(),
+		role: Some(if owned {
+			ProjectRole::Owner
+		} else {
+			ProjectRole::Contributor
+		}),
+	})
+}
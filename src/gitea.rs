@@ -0,0 +1,174 @@
+//! Import of projects from Gitea compatible forges, such as Forgejo and Codeberg.
+use crate::forge::{get_all_pages, get_json, language_stats, path_segment};
+use crate::{Project, ProjectRole};
+use ::serde::Deserialize;
+use ::std::collections::HashMap;
+use anyhow::Error;
+use futures::stream::TryStreamExt;
+
+pub(crate) const DEFAULT_BASE_URL: &str = "https://codeberg.org";
+
+#[derive(Deserialize)]
+struct User {
+	login: String,
+}
+
+#[derive(Deserialize)]
+struct Repository {
+	name: String,
+	full_name: String,
+	description: Option<String>,
+	html_url: String,
+	#[serde(default)]
+	stars_count: u64,
+	#[serde(default)]
+	forks_count: u64,
+	#[serde(default)]
+	archived: bool,
+	#[serde(default)]
+	fork: bool,
+	/// Only returned by newer versions of Gitea
+	#[serde(default)]
+	topics: Option<Vec<String>>,
+	owner: User,
+}
+
+struct Client {
+	api: String,
+	headers: Vec<(&'static str, String)>,
+}
+
+impl Client {
+	fn new(base_url: &str, token: Option<String>) -> Self {
+		let token = token.or_else(|| std::env::var("GITEA_TOKEN").ok());
+		Self {
+			api: format!("{}/api/v1", base_url.trim_end_matches('/')),
+			headers: token
+				.into_iter()
+				.map(|token| ("Authorization", format!("token {}", token)))
+				.collect(),
+		}
+	}
+
+	async fn current_user(&self) -> Result<String, Error> {
+		if self.headers.is_empty() {
+			anyhow::bail!("No Gitea user given, and no token to find the current user with");
+		}
+		let user: User = get_json(&format!("{}/user", self.api), &self.headers).await?;
+		Ok(user.login)
+	}
+
+	async fn project(&self, r: Repository, role: ProjectRole) -> Result<Project, Error> {
+		let (owner, name) = r
+			.full_name
+			.split_once('/')
+			.unwrap_or((&r.owner.login, &r.name));
+		let repo_url = format!(
+			"{}/repos/{}/{}",
+			self.api,
+			path_segment(owner),
+			path_segment(name)
+		);
+		// Bytes of code in each language
+		let languages: HashMap<String, u64> =
+			get_json(&format!("{}/languages", repo_url), &self.headers).await?;
+		let topics = match r.topics {
+			Some(topics) => topics,
+			None => {
+				#[derive(Deserialize)]
+				struct Topics {
+					topics: Vec<String>,
+				}
+				let topics: Topics =
+					get_json(&format!("{}/topics", repo_url), &self.headers).await?;
+				topics.topics
+			}
+		};
+		Ok(Project {
+			name: r.name,
+			description: r.description.filter(|d| !d.is_empty()),
+			contributions: None,
+			url: r.html_url.parse().ok(),
+			stars: Some(r.stars_count),
+			forks: Some(r.forks_count),
+			active: Some(!r.archived),
+			owner: Some(r.owner.login),
+			commits: None,
+			additions: None,
+			deletions: None,
+			languages: language_stats(languages),
+			tags: topics,
+			role: Some(role),
+		})
+	}
+}
+
+/// Get the repositories owned by `user`, or by the owner of the token if no user is given.
+pub(crate) async fn get_user_projects_from_gitea(
+	base_url: &str,
+	user: Option<&str>,
+	ignore_forks: bool,
+	token: Option<String>,
+) -> Result<Vec<Project>, Error> {
+	let client = Client::new(base_url, token);
+	let url = match user {
+		Some(user) => format!("{}/users/{}/repos?limit=50", client.api, path_segment(user)),
+		None => format!("{}/user/repos?limit=50", client.api),
+	};
+	let user = match user {
+		Some(user) => user.to_owned(),
+		None => client.current_user().await?,
+	};
+	let repos: Vec<Repository> = get_all_pages(&url, &client.headers).await?;
+	let st: futures::stream::FuturesUnordered<_> = repos
+		.into_iter()
+		// The token owner's list also contains repositories they can only access. Logins are
+		// case-insensitive.
+		.filter(|r| r.owner.login.eq_ignore_ascii_case(&user))
+		.filter(|r| !ignore_forks || !r.fork)
+		.map(|r| client.project(r, ProjectRole::Owner))
+		.collect();
+	st.try_collect().await
+}
+
+/// Get the repositories named in `repos`, as `owner/repo`. Repositories not owned by `user`, or
+/// by the owner of the token if no user is given, are considered contributions.
+pub(crate) async fn get_projects_info_from_gitea<I>(
+	base_url: &str,
+	repos: I,
+	user: Option<&str>,
+	token: Option<String>,
+) -> Result<Vec<Project>, Error>
+where
+	I: IntoIterator,
+	<I as IntoIterator>::Item: AsRef<str>,
+{
+	let client = Client::new(base_url, token);
+	let client = &client;
+	let current_user = match user {
+		Some(user) => user.to_owned(),
+		None => client.current_user().await?,
+	};
+	let current_user = current_user.as_str();
+	let st: futures::stream::FuturesUnordered<_> = repos
+		.into_iter()
+		.filter_map(|v| {
+			let (o, r) = v.as_ref().split_once('/')?;
+			Some((path_segment(o), path_segment(r)))
+		})
+		.map(|(o, r)| async move {
+			let r: Repository = get_json(
+				&format!("{}/repos/{}/{}", client.api, o, r),
+				&client.headers,
+			)
+			.await?;
+			let role = if r.owner.login.eq_ignore_ascii_case(current_user) {
+				ProjectRole::Owner
+			} else {
+				ProjectRole::Contributor
+			};
+			client.project(r, role).await
+		})
+		.collect();
+	st.try_collect().await
+}
@@ -1,14 +1,184 @@
-use crate::Project;
-use futures::stream::{Stream, StreamExt, TryStreamExt};
-use futures::FutureExt;
+use crate::{Project, ProjectRole};
+use futures::stream::{StreamExt, TryStreamExt};
 use octocrab::OctocrabBuilder;
 use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::{Duration, SystemTime};
 
-pub(crate) async fn get_user_projects_from_github(
-	ignore_forks: bool,
-	token: Option<String>,
-) -> anyhow::Result<Vec<Project>> {
+pub(crate) const DEFAULT_BASE_URL: &str = "https://api.github.com";
+
+/// How many repositories are fetched at the same time
+const MAX_CONCURRENT_REQUESTS: usize = 8;
+/// How many times a request is retried, after being rate limited or a server error
+const MAX_RETRIES: u32 = 5;
+/// Give up instead of waiting longer than this for the rate limit to reset
+const MAX_WAIT: Duration = Duration::from_secs(15 * 60);
+
+/// Like octocrab's `BaseUriLayer`, but leaves absolute URIs, such as links to the next page,
+/// alone, since they already include the path of the API.
+#[derive(Clone)]
+struct ApiUriLayer {
+	base: String,
+}
+
+impl<S> tower::Layer<S> for ApiUriLayer {
+	type Service = ApiUri<S>;
+	fn layer(&self, inner: S) -> Self::Service {
+		ApiUri {
+			base: self.base.clone(),
+			inner,
+		}
+	}
+}
+
+#[derive(Clone)]
+struct ApiUri<S> {
+	base: String,
+	inner: S,
+}
+
+impl<S, B> tower::Service<http::Request<B>> for ApiUri<S>
+where
+	S: tower::Service<http::Request<B>>,
+{
+	type Response = S::Response;
+	type Error = S::Error;
+	type Future = S::Future;
+
+	fn poll_ready(
+		&mut self,
+		cx: &mut std::task::Context<'_>,
+	) -> std::task::Poll<Result<(), Self::Error>> {
+		self.inner.poll_ready(cx)
+	}
+
+	fn call(&mut self, mut req: http::Request<B>) -> Self::Future {
+		if req.uri().authority().is_none() {
+			let path = req.uri().path_and_query().map_or("/", |p| p.as_str());
+			let uri = match (path, self.base.strip_suffix("/v3")) {
+				// GitHub Enterprise Server has the GraphQL API at /api/graphql
+				("/graphql", Some(api)) => format!("{}{}", api, path),
+				_ => format!("{}{}", self.base, path),
+			};
+			*req.uri_mut() = uri.parse().expect("Valid Uri");
+		}
+		self.inner.call(req)
+	}
+}
+
+/// Retries requests that are rate limited, waiting as long as GitHub asks, and those that
+/// fail because of server errors, with exponential backoff.
+#[derive(Clone)]
+struct RateLimitLayer;
+
+impl<S> tower::Layer<S> for RateLimitLayer {
+	type Service = RateLimit<S>;
+	fn layer(&self, inner: S) -> Self::Service {
+		RateLimit { inner }
+	}
+}
+
+#[derive(Clone)]
+struct RateLimit<S> {
+	inner: S,
+}
+
+fn header<T: std::str::FromStr, B>(resp: &http::Response<B>, name: &str) -> Option<T> {
+	resp.headers().get(name)?.to_str().ok()?.parse().ok()
+}
+
+/// How long to wait before retrying the request that got `resp`, if it should be retried.
+fn retry_after<B>(resp: &http::Response<B>, attempt: u32) -> Option<Duration> {
+	use http::StatusCode;
+	let backoff = Duration::from_secs(1 << attempt);
+	match resp.status() {
+		StatusCode::FORBIDDEN | StatusCode::TOO_MANY_REQUESTS => {
+			if let Some(seconds) = header::<u64, _>(resp, "retry-after") {
+				return Some(Duration::from_secs(seconds));
+			}
+			if header::<u64, _>(resp, "x-ratelimit-remaining") == Some(0) {
+				let reset: u64 = header(resp, "x-ratelimit-reset")?;
+				let now = SystemTime::now()
+					.duration_since(SystemTime::UNIX_EPOCH)
+					.ok()?
+					.as_secs();
+				return Some(Duration::from_secs(reset.saturating_sub(now) + 1));
+			}
+			// Secondary rate limits don't always say how long to wait, GitHub recommends at
+			// least a minute. A 403 without rate limit headers is a permission problem.
+			(resp.status() == StatusCode::TOO_MANY_REQUESTS)
+				.then(|| Duration::from_secs(60) + backoff)
+		}
+		StatusCode::BAD_GATEWAY
+		| StatusCode::SERVICE_UNAVAILABLE
+		| StatusCode::GATEWAY_TIMEOUT => Some(backoff),
+		_ => None,
+	}
+}
+
+impl<S, B> tower::Service<http::Request<String>> for RateLimit<S>
+where
+	S: tower::Service<http::Request<String>, Response = http::Response<B>>
+		+ Clone
+		+ Send
+		+ 'static,
+	S::Future: Send,
+	S::Error: Send,
+	B: Send + 'static,
+{
+	type Response = S::Response;
+	type Error = S::Error;
+	type Future = futures::future::BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+	fn poll_ready(
+		&mut self,
+		cx: &mut std::task::Context<'_>,
+	) -> std::task::Poll<Result<(), Self::Error>> {
+		self.inner.poll_ready(cx)
+	}
+
+	fn call(&mut self, req: http::Request<String>) -> Self::Future {
+		use tower::ServiceExt;
+		// Use the service that is ready for the first try
+		let clone = self.inner.clone();
+		let mut inner = std::mem::replace(&mut self.inner, clone);
+		Box::pin(async move {
+			let (parts, body) = req.into_parts();
+			let mut attempt = 0;
+			loop {
+				let mut req = http::Request::new(body.clone());
+				*req.method_mut() = parts.method.clone();
+				*req.uri_mut() = parts.uri.clone();
+				*req.version_mut() = parts.version;
+				*req.headers_mut() = parts.headers.clone();
+				let resp = inner.ready().await?.call(req).await?;
+				if let (Some(remaining), Some(limit)) = (
+					header::<u64, _>(&resp, "x-ratelimit-remaining"),
+					header::<u64, _>(&resp, "x-ratelimit-limit"),
+				) {
+					log::debug!("GitHub API quota: {}/{} remaining", remaining, limit);
+				}
+				let wait = match retry_after(&resp, attempt) {
+					Some(wait) if attempt < MAX_RETRIES && wait <= MAX_WAIT => wait,
+					_ => return Ok(resp),
+				};
+				log::warn!(
+					"{} {} returned {}, retrying in {}s",
+					parts.method,
+					parts.uri,
+					resp.status(),
+					wait.as_secs()
+				);
+				attempt += 1;
+				tokio::time::sleep(wait).await;
+			}
+		})
+	}
+}
+
+/// Create a client for the GitHub API at `base_url`, authenticated with `token`, or
+/// `$GITHUB_TOKEN`.
+fn client(base_url: &str, token: Option<String>) -> anyhow::Result<octocrab::Octocrab> {
 	use octocrab::service::middleware;
 	let connector = hyper_rustls::HttpsConnectorBuilder::new()
 		.with_native_roots() // enabled the `rustls-native-certs` feature in hyper-rustls
@@ -16,15 +186,27 @@ pub(crate) async fn get_user_projects_from_github(
 		.enable_http1()
 		.build();
 
+	let base = base_url.trim_end_matches('/');
+	// Request paths are appended to it, so it has to be an absolute URL
+	match base.parse::<http::Uri>() {
+		Ok(uri) if uri.scheme().is_some() && uri.authority().is_some() => {}
+		Ok(_) => anyhow::bail!(
+			"Invalid GitHub API URL {}: missing scheme or host",
+			base_url
+		),
+		Err(e) => anyhow::bail!("Invalid GitHub API URL {}: {}", base_url, e),
+	}
 	// When running from github actions
-	let token = token.unwrap_or_else(|| std::env::var("GITHUB_TOKEN").unwrap());
-
+	let token = match token.or_else(|| std::env::var("GITHUB_TOKEN").ok()) {
+		Some(token) => token,
+		None => anyhow::bail!("A GitHub token is required, set `token` or GITHUB_TOKEN"),
+	};
 	let client = hyper::Client::builder().build(connector);
-	let gh = OctocrabBuilder::new_empty()
+	Ok(OctocrabBuilder::new_empty()
 		.with_service(client)
-		.with_layer(&middleware::base_uri::BaseUriLayer::new(
-			http::Uri::from_static("https://api.github.com"),
-		))
+		.with_layer(&ApiUriLayer {
+			base: base.to_owned(),
+		})
 		.with_layer(&middleware::extra_headers::ExtraHeadersLayer::new(
 			Arc::new(vec![
 				(http::header::USER_AGENT, "briefly/0.0".parse().unwrap()),
@@ -34,69 +216,208 @@ pub(crate) async fn get_user_projects_from_github(
 				),
 			]),
 		))
+		.with_layer(&RateLimitLayer)
 		.with_auth(octocrab::AuthState::None)
-		.build()?;
-	let mut repos = gh
+		.build()?)
+}
+
+/// Commits, additions and deletions `user` made to `owner/repo`, from GitHub's contributor
+/// statistics. `None` if GitHub does not have them.
+async fn contributor_stats(
+	gh: &octocrab::Octocrab,
+	owner: &str,
+	repo: &str,
+	user: &str,
+) -> anyhow::Result<Option<(u64, u64, u64)>> {
+	#[derive(serde::Deserialize)]
+	struct Week {
+		a: u64,
+		d: u64,
+		c: u64,
+	}
+	#[derive(serde::Deserialize)]
+	struct Author {
+		login: String,
+	}
+	#[derive(serde::Deserialize)]
+	struct Contributor {
+		author: Option<Author>,
+		weeks: Vec<Week>,
+	}
+	let url = format!("/repos/{}/{}/stats/contributors", owner, repo);
+	// GitHub computes the statistics in the background, and answers 202 until they are ready
+	let mut retries = 5;
+	let resp = loop {
+		let resp = gh._get(url.as_str()).await?;
+		if resp.status() != http::StatusCode::ACCEPTED || retries == 0 {
+			break resp;
+		}
+		retries -= 1;
+		tokio::time::sleep(std::time::Duration::from_secs(2)).await;
+	};
+	if resp.status() != http::StatusCode::OK {
+		log::warn!(
+			"No contributor statistics for {}/{}: {}",
+			owner,
+			repo,
+			resp.status()
+		);
+		return Ok(None);
+	}
+	let body = hyper::body::to_bytes(resp.into_body()).await?;
+	let contributors: Vec<Contributor> = serde_json::from_slice(&body)?;
+	Ok(contributors
+		.into_iter()
+		.find(|c| {
+			c.author
+				.as_ref()
+				.is_some_and(|a| a.login.eq_ignore_ascii_case(user))
+		})
+		.map(|c| {
+			c.weeks
+				.iter()
+				.fold((0, 0, 0), |(c, a, d), w| (c + w.c, a + w.a, d + w.d))
+		}))
+}
+
+/// Convert a repository into a project, with the languages used in it.
+async fn project(
+	gh: &octocrab::Octocrab,
+	repo: octocrab::models::Repository,
+	role: ProjectRole,
+) -> anyhow::Result<Project> {
+	let mut languages = Vec::new();
+	if let Some(languages_url) = &repo.languages_url {
+		use hyper::body::HttpBody;
+		let mut languages_resp = gh._get(languages_url.to_string()).await?;
+		let value: HashMap<String, u64> = serde_json::from_slice(
+			&languages_resp
+				.body_mut()
+				.data()
+				.await
+				.transpose()?
+				.unwrap_or_default(),
+		)?;
+		languages = crate::forge::language_stats(value);
+	}
+	Ok(Project {
+		name: repo.name,
+		description: repo.description,
+		contributions: None,
+		url: repo.html_url,
+		stars: repo.stargazers_count.map(|v| v as u64),
+		forks: repo.forks_count.map(|v| v as u64),
+		active: repo.archived.map(|v| !v),
+		owner: repo.owner.map(|o| o.login),
+		commits: None,
+		additions: None,
+		deletions: None,
+		languages,
+		tags: repo.topics.unwrap_or_default(),
+		role: Some(role),
+	})
+}
+
+/// Get all pages of a list, starting from `page`.
+async fn all_pages<T: serde::de::DeserializeOwned>(
+	gh: &octocrab::Octocrab,
+	mut page: octocrab::Page<T>,
+) -> anyhow::Result<Vec<T>> {
+	let mut ret = Vec::new();
+	loop {
+		ret.append(&mut page.items);
+		let Some(next_page) = gh.get_page(&page.next).await? else {
+			break;
+		};
+		page = next_page;
+	}
+	Ok(ret)
+}
+
+pub(crate) async fn get_user_projects_from_github(
+	base_url: &str,
+	ignore_forks: bool,
+	token: Option<String>,
+) -> anyhow::Result<Vec<Project>> {
+	let gh = client(base_url, token)?;
+	let repos = gh
 		.current()
 		.list_repos_for_authenticated_user()
 		.send()
 		.await?;
 	let mut ret = Vec::new();
-	loop {
-		for repo in &repos {
-			if ignore_forks && repo.fork.unwrap_or(false) {
-				continue;
-			}
-			let mut languages = Vec::new();
-			if let Some(languages_url) = &repo.languages_url {
-				use hyper::body::HttpBody;
-				let mut languages_resp = gh._get(languages_url.to_string()).await?;
-				let value: HashMap<String, u64> = serde_json::from_slice(
-					&languages_resp
-						.body_mut()
-						.data()
-						.await
-						.transpose()?
-						.unwrap_or_default(),
-				)?;
-				let mut total = 0f64;
-				for (_, v) in &value {
-					total += *v as f64;
-				}
-				for (k, v) in &value {
-					languages.push(crate::LanguageStat {
-						language: k.clone(),
-						percentage: (*v as f64 / total * 100.).into(),
-					});
-				}
-			}
-			languages.sort_by(|a, b| b.percentage.partial_cmp(&a.percentage).unwrap());
-			ret.push(Project {
-				name: repo.name.clone(),
-				description: repo.description.clone(),
-				contributions: None,
-				url: repo.html_url.clone(),
-				stars: repo.stargazers_count.map(|v| v as u64),
-				forks: repo.forks_count.map(|v| v as u64),
-				active: repo.archived.map(|v| !v),
-				owner: Some(repo.owner.clone().map(|o| o.login).unwrap_or_default()),
-				commits: None,
-				additions: None,
-				deletions: None,
-				languages,
-				tags: repo.topics.clone().unwrap_or_default(),
-				role: Some(crate::ProjectRole::Owner),
-			})
+	for repo in all_pages(&gh, repos).await? {
+		if ignore_forks && repo.fork.unwrap_or(false) {
+			continue;
 		}
-		let Some(next_repos) = gh.get_page(&repos.next).await? else {
-            break;
-        };
-		repos = next_repos;
+		ret.push(project(&gh, repo, ProjectRole::Owner).await?);
 	}
 	Ok(ret)
 }
 
-pub(crate) async fn get_projects_info_from_github<'a, I>(
+/// Get the repositories of `organizations`, and of `teams`, given as `org/team`. The projects
+/// are maintained if the user can administer or maintain them, and contributions if they can
+/// push to them. Other repositories are left out.
+pub(crate) async fn get_organization_projects_from_github(
+	base_url: &str,
+	organizations: &[String],
+	teams: &[String],
+	ignore_forks: bool,
+	token: Option<String>,
+) -> anyhow::Result<Vec<Project>> {
+	let gh = client(base_url, token)?;
+	let mut repos = Vec::new();
+	for org in organizations {
+		let page = gh.orgs(org).list_repos().per_page(100).send().await?;
+		repos.extend(all_pages(&gh, page).await?);
+	}
+	for team in teams {
+		let Some((org, team)) = team.split_once('/') else {
+			return Err(anyhow::anyhow!(
+				"Team {} should be written as org/team",
+				team
+			));
+		};
+		let page = gh
+			.get(
+				format!("/orgs/{}/teams/{}/repos", org, team),
+				Some(&[("per_page", 100)]),
+			)
+			.await?;
+		repos.extend(all_pages(&gh, page).await?);
+	}
+	// A repository can belong to several of the teams
+	let mut seen = std::collections::HashSet::new();
+	repos.retain(|r: &octocrab::models::Repository| seen.insert(r.id));
+
+	let mut ret = Vec::new();
+	for repo in repos {
+		if ignore_forks && repo.fork.unwrap_or(false) {
+			continue;
+		}
+		// Repositories the user can't push to are not their work
+		let role = match &repo.permissions {
+			Some(p) if p.admin || p.maintain => ProjectRole::Maintainer,
+			Some(p) if p.push => ProjectRole::Contributor,
+			_ => continue,
+		};
+		ret.push(project(&gh, repo, role).await?);
+	}
+	Ok(ret)
+}
+
+/// Split a repository given as `owner/repo` into its owner and name. Anything after the
+/// second segment is ignored.
+fn owner_and_repo(v: &str) -> Option<(String, String)> {
+	if let &[o, r] = v.split('/').take(2).collect::<Vec<_>>().as_slice() {
+		Some((o.to_owned(), r.to_owned()))
+	} else {
+		None
+	}
+}
+
+pub(crate) async fn get_projects_info_from_github<I>(
+	base_url: &str,
 	repos: I,
 	token: Option<String>,
 	user: Option<String>,
@@ -105,99 +426,456 @@ where
 	I: IntoIterator,
 	<I as IntoIterator>::Item: AsRef<str>,
 {
-	use octocrab::service::middleware;
-	let connector = hyper_rustls::HttpsConnectorBuilder::new()
-		.with_native_roots() // enabled the `rustls-native-certs` feature in hyper-rustls
-		.https_only()
-		.enable_http1()
-		.build();
-
-	// When running from github actions
-	let token = token.unwrap_or_else(|| std::env::var("GITHUB_TOKEN").unwrap());
-	let client = hyper::Client::builder().build(connector);
-	let gh = OctocrabBuilder::new_empty()
-		.with_service(client)
-		.with_layer(&middleware::base_uri::BaseUriLayer::new(
-			http::Uri::from_static("https://api.github.com"),
-		))
-		.with_layer(&middleware::extra_headers::ExtraHeadersLayer::new(
-			Arc::new(vec![
-				(http::header::USER_AGENT, "briefly/0.0".parse().unwrap()),
-				(
-					http::header::AUTHORIZATION,
-					format!("Bearer {}", token).parse().unwrap(),
-				),
-			]),
-		))
-		.with_auth(octocrab::AuthState::None)
-		.build()?;
+	let gh = client(base_url, token)?;
 	let current_user = if let Some(user) = user {
 		user
 	} else {
 		gh.current().user().await?.login
 	};
-	let st: futures::stream::FuturesUnordered<_> = repos
+	let repos: Vec<_> = repos
 		.into_iter()
-		.filter_map(|v| {
-			if let &[o, r] = v.as_ref().split("/").take(2).collect::<Vec<_>>().as_slice() {
-				Some((o.to_owned(), r.to_owned()))
-			} else {
-				None
-			}
-		})
+		.filter_map(|v| owner_and_repo(v.as_ref()))
+		.collect();
+	futures::stream::iter(repos)
 		.map(|(o, r)| {
 			let gh = gh.clone();
 			let current_user = current_user.clone();
 			async move {
 				let r = gh.repos(o, r).get().await?;
-				let mut languages = Vec::new();
-				if let Some(languages_url) = &r.languages_url {
-					use hyper::body::HttpBody;
-					let mut languages_resp = gh._get(languages_url.to_string()).await?;
-					let value: HashMap<String, u64> = serde_json::from_slice(
-						&languages_resp
-							.body_mut()
-							.data()
-							.await
-							.transpose()?
-							.unwrap_or_default(),
-					)?;
-					let mut total = 0f64;
-					for v in value.values() {
-						total += *v as f64;
-					}
-					for (k, v) in &value {
-						languages.push(crate::LanguageStat {
-							language: k.clone(),
-							percentage: (*v as f64 / total * 100.).into(),
-						});
+				let owner = r.owner.clone().unwrap().login;
+				let role = if owner == current_user {
+					ProjectRole::Owner
+				} else {
+					ProjectRole::Contributor
+				};
+				let mut p = project(&gh, r, role).await?;
+				if role == ProjectRole::Contributor {
+					if let Some((commits, additions, deletions)) =
+						contributor_stats(&gh, &owner, &p.name, &current_user).await?
+					{
+						p.commits = Some(commits);
+						p.additions = Some(additions);
+						p.deletions = Some(deletions);
 					}
 				}
-				languages.sort_by(|a, b| b.percentage.partial_cmp(&a.percentage).unwrap());
+				Result::<_, anyhow::Error>::Ok(p)
+			}
+		})
+		// Many concurrent requests trigger GitHub's secondary rate limits
+		.buffer_unordered(MAX_CONCURRENT_REQUESTS)
+		.try_collect()
+		.await
+}
 
-				let owner = r.owner.clone().unwrap().login;
-				Result::<_, anyhow::Error>::Ok(Project {
-					name: r.name,
-					description: r.description,
-					contributions: None,
-					url: r.html_url.clone(),
-					stars: r.stargazers_count.map(|v| v as _),
-					forks: r.forks_count.map(|v| v as _),
-					active: r.archived.map(|archived| !archived),
-					owner: r.owner.clone().map(|o| o.login),
-					additions: None,
-					deletions: None,
-					commits: None,
-					languages,
-					tags: r.topics.unwrap_or_default(),
-					role: Some(if owner == current_user {
-						crate::ProjectRole::Owner
-					} else {
-						crate::ProjectRole::Contributor
-					}),
-				})
+/// Find the repositories `user`, or the owner of the token, contributed to with merged pull
+/// requests. Repositories with fewer than `min_pull_requests` of them, or listed in
+/// `exclude`, are left out. The pull requests are summarized in `contributions`.
+pub(crate) async fn get_contributions_from_github(
+	base_url: &str,
+	user: Option<String>,
+	min_pull_requests: usize,
+	exclude: &[String],
+	token: Option<String>,
+) -> anyhow::Result<Vec<Project>> {
+	/// How many pull requests are listed in the summary
+	const LISTED: usize = 5;
+	let gh = client(base_url, token.clone())?;
+	let user = if let Some(user) = user {
+		user
+	} else {
+		gh.current().user().await?.login
+	};
+	// Pull requests to the user's own repositories are not contributions
+	let query = format!("type:pr is:merged author:{} -user:{}", user, user);
+	let mut page = gh
+		.search()
+		.issues_and_pull_requests(&query)
+		.sort("created")
+		.per_page(100)
+		.send()
+		.await?;
+	let mut pull_requests: HashMap<String, Vec<octocrab::models::issues::Issue>> =
+		HashMap::new();
+	loop {
+		for pr in page.items.drain(..) {
+			// https://api.github.com/repos/{owner}/{repo}
+			let Some(mut segments) = pr.repository_url.path_segments() else {
+				continue;
+			};
+			let (Some(name), Some(owner)) = (segments.next_back(), segments.next_back())
+			else {
+				continue;
+			};
+			let repo = format!("{}/{}", owner, name);
+			pull_requests.entry(repo).or_default().push(pr);
+		}
+		let Some(next_page) = gh.get_page(&page.next).await? else {
+			break;
+		};
+		page = next_page;
+	}
+	pull_requests.retain(|repo, prs| {
+		prs.len() >= min_pull_requests.max(1)
+			&& !exclude.iter().any(|e| e.eq_ignore_ascii_case(repo))
+	});
+
+	let mut projects =
+		get_projects_info_from_github(base_url, pull_requests.keys(), token, Some(user))
+			.await?;
+	for p in &mut projects {
+		let repo = format!("{}/{}", p.owner.as_deref().unwrap_or_default(), p.name);
+		let Some(prs) = pull_requests
+			.iter()
+			.find(|(k, _)| k.eq_ignore_ascii_case(&repo))
+			.map(|(_, v)| v)
+		else {
+			continue;
+		};
+		let listed: Vec<_> = prs
+			.iter()
+			.take(LISTED)
+			.map(|pr| format!("[{}]({})", crate::markdown::escape(&pr.title), pr.html_url))
+			.collect();
+		let mut summary = format!(
+			"{} merged pull request{}: {}",
+			prs.len(),
+			if prs.len() == 1 { "" } else { "s" },
+			listed.join(", ")
+		);
+		if prs.len() > LISTED {
+			summary.push_str(&format!(" and {} more", prs.len() - LISTED));
+		}
+		p.contributions = Some(summary);
+		// Even if the user has since become an owner, this is a contribution
+		p.role = Some(ProjectRole::Contributor);
+	}
+	Ok(projects)
+}
+
+/// The fields of a repository needed to make a project, for the GraphQL API.
+const REPOSITORY_FRAGMENT: &str = "
+fragment Repo on Repository {
+	name
+	description
+	url
+	stargazerCount
+	forkCount
+	isArchived
+	isFork
+	viewerPermission
+	owner { login }
+	repositoryTopics(first: 100) { nodes { topic { name } } }
+	languages(first: 100) { edges { size node { name } } }
+}";
+
+#[derive(serde::Deserialize)]
+struct Nodes<T> {
+	nodes: Vec<T>,
+}
+
+#[derive(serde::Deserialize)]
+struct Named {
+	name: String,
+}
+
+#[derive(serde::Deserialize)]
+struct Login {
+	login: String,
+}
+
+#[derive(serde::Deserialize)]
+struct Topic {
+	topic: Named,
+}
+
+#[derive(serde::Deserialize)]
+struct LanguageEdge {
+	size: u64,
+	node: Named,
+}
+
+#[derive(serde::Deserialize)]
+struct Languages {
+	edges: Vec<LanguageEdge>,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GraphQLRepository {
+	name: String,
+	description: Option<String>,
+	url: url::Url,
+	stargazer_count: u64,
+	fork_count: u64,
+	is_archived: bool,
+	is_fork: bool,
+	viewer_permission: Option<String>,
+	owner: Login,
+	repository_topics: Nodes<Topic>,
+	languages: Option<Languages>,
+}
+
+impl GraphQLRepository {
+	fn into_project(self, role: ProjectRole) -> Project {
+		let languages = self
+			.languages
+			.map(|l| l.edges.into_iter().map(|e| (e.node.name, e.size)).collect())
+			.unwrap_or_default();
+		Project {
+			name: self.name,
+			description: self.description,
+			contributions: None,
+			url: Some(self.url),
+			stars: Some(self.stargazer_count),
+			forks: Some(self.fork_count),
+			active: Some(!self.is_archived),
+			owner: Some(self.owner.login),
+			commits: None,
+			additions: None,
+			deletions: None,
+			languages: crate::forge::language_stats(languages),
+			tags: self
+				.repository_topics
+				.nodes
+				.into_iter()
+				.map(|t| t.topic.name)
+				.collect(),
+			role: Some(role),
+		}
+	}
+}
+
+/// Run a GraphQL query, and return its data.
+async fn graphql(
+	gh: &octocrab::Octocrab,
+	query: &str,
+	variables: serde_json::Value,
+) -> anyhow::Result<serde_json::Value> {
+	#[derive(serde::Deserialize)]
+	struct Error {
+		message: String,
+	}
+	#[derive(serde::Deserialize)]
+	struct Response {
+		data: Option<serde_json::Value>,
+		#[serde(default)]
+		errors: Vec<Error>,
+	}
+	let query = format!("{}\n{}", query, REPOSITORY_FRAGMENT);
+	let resp: Response = gh
+		.graphql(&serde_json::json!({ "query": query, "variables": variables }))
+		.await?;
+	if !resp.errors.is_empty() {
+		let messages: Vec<_> = resp.errors.into_iter().map(|e| e.message).collect();
+		return Err(anyhow::anyhow!(
+			"GraphQL query failed: {}",
+			messages.join("; ")
+		));
+	}
+	resp.data
+		.ok_or_else(|| anyhow::anyhow!("GraphQL query returned no data"))
+}
+
+/// Get all repositories in a paginated connection. `query` takes the `$cursor` to start from,
+/// and `connection` is where the connection is in the result, as a JSON pointer.
+async fn graphql_repositories(
+	gh: &octocrab::Octocrab,
+	query: &str,
+	mut variables: serde_json::Value,
+	connection: &str,
+) -> anyhow::Result<Vec<GraphQLRepository>> {
+	#[derive(serde::Deserialize)]
+	#[serde(rename_all = "camelCase")]
+	struct PageInfo {
+		has_next_page: bool,
+		end_cursor: Option<String>,
+	}
+	#[derive(serde::Deserialize)]
+	#[serde(rename_all = "camelCase")]
+	struct Connection {
+		page_info: PageInfo,
+		nodes: Vec<GraphQLRepository>,
+	}
+	let mut ret = Vec::new();
+	loop {
+		let mut data = graphql(gh, query, variables.clone()).await?;
+		let connection: Connection = match data.pointer_mut(connection) {
+			Some(c) if !c.is_null() => serde_json::from_value(c.take())?,
+			_ => return Err(anyhow::anyhow!("{} not found in {}", connection, variables)),
+		};
+		ret.extend(connection.nodes);
+		match connection.page_info.end_cursor {
+			Some(cursor) if connection.page_info.has_next_page => {
+				variables["cursor"] = cursor.into();
+			}
+			_ => break,
+		}
+	}
+	Ok(ret)
+}
+
+/// Like `get_user_projects_from_github`, using the GraphQL API.
+pub(crate) async fn get_user_projects_from_github_graphql(
+	base_url: &str,
+	ignore_forks: bool,
+	token: Option<String>,
+) -> anyhow::Result<Vec<Project>> {
+	let gh = client(base_url, token)?;
+	let query = "
+query($cursor: String) {
+	viewer {
+		repositories(
+			first: 100,
+			after: $cursor,
+			ownerAffiliations: [OWNER, COLLABORATOR, ORGANIZATION_MEMBER]
+		) {
+			pageInfo { hasNextPage endCursor }
+			nodes { ...Repo }
+		}
+	}
+}";
+	let repos =
+		graphql_repositories(&gh, query, serde_json::json!({}), "/viewer/repositories")
+			.await?;
+	Ok(repos
+		.into_iter()
+		.filter(|r| !ignore_forks || !r.is_fork)
+		.map(|r| r.into_project(ProjectRole::Owner))
+		.collect())
+}
+
+/// Like `get_organization_projects_from_github`, using the GraphQL API.
+pub(crate) async fn get_organization_projects_from_github_graphql(
+	base_url: &str,
+	organizations: &[String],
+	teams: &[String],
+	ignore_forks: bool,
+	token: Option<String>,
+) -> anyhow::Result<Vec<Project>> {
+	let gh = client(base_url, token)?;
+	let mut repos = Vec::new();
+	for org in organizations {
+		let query = "
+query($org: String!, $cursor: String) {
+	organization(login: $org) {
+		repositories(first: 100, after: $cursor) {
+			pageInfo { hasNextPage endCursor }
+			nodes { ...Repo }
+		}
+	}
+}";
+		let variables = serde_json::json!({ "org": org });
+		repos.extend(
+			graphql_repositories(&gh, query, variables, "/organization/repositories").await?,
+		);
+	}
+	for team in teams {
+		let Some((org, team)) = team.split_once('/') else {
+			return Err(anyhow::anyhow!(
+				"Team {} should be written as org/team",
+				team
+			));
+		};
+		let query = "
+query($org: String!, $team: String!, $cursor: String) {
+	organization(login: $org) {
+		team(slug: $team) {
+			repositories(first: 100, after: $cursor) {
+				pageInfo { hasNextPage endCursor }
+				nodes { ...Repo }
 			}
+		}
+	}
+}";
+		let variables = serde_json::json!({ "org": org, "team": team });
+		repos.extend(
+			graphql_repositories(&gh, query, variables, "/organization/team/repositories")
+				.await?,
+		);
+	}
+	// A repository can belong to several of the teams
+	let mut seen = std::collections::HashSet::new();
+	repos.retain(|r| seen.insert(r.url.clone()));
+	Ok(repos
+		.into_iter()
+		.filter(|r| !ignore_forks || !r.is_fork)
+		.filter_map(|r| {
+			// Repositories the user can't push to are not their work
+			let role = match r.viewer_permission.as_deref() {
+				Some("ADMIN" | "MAINTAIN") => ProjectRole::Maintainer,
+				Some("WRITE") => ProjectRole::Contributor,
+				_ => return None,
+			};
+			Some(r.into_project(role))
 		})
+		.collect())
+}
+
+/// Like `get_projects_info_from_github`, using the GraphQL API to fetch many repositories with
+/// one request.
+pub(crate) async fn get_projects_info_from_github_graphql<I>(
+	base_url: &str,
+	repos: I,
+	token: Option<String>,
+	user: Option<String>,
+) -> anyhow::Result<Vec<Project>>
+where
+	I: IntoIterator,
+	<I as IntoIterator>::Item: AsRef<str>,
+{
+	/// How many repositories are fetched in one request
+	const BATCH: usize = 50;
+	let gh = client(base_url, token)?;
+	let current_user = if let Some(user) = user {
+		user
+	} else {
+		gh.current().user().await?.login
+	};
+	let repos: Vec<_> = repos
+		.into_iter()
+		.filter_map(|v| owner_and_repo(v.as_ref()))
 		.collect();
-	st.try_collect().await
+	let mut ret = Vec::new();
+	for batch in repos.chunks(BATCH) {
+		let mut parameters = Vec::new();
+		let mut fields = Vec::new();
+		let mut variables = serde_json::Map::new();
+		for (i, (o, r)) in batch.iter().enumerate() {
+			parameters.push(format!("$o{i}: String!, $r{i}: String!"));
+			fields.push(format!(
+				"r{i}: repository(owner: $o{i}, name: $r{i}) {{ ...Repo }}"
+			));
+			variables.insert(format!("o{i}"), o.as_str().into());
+			variables.insert(format!("r{i}"), r.as_str().into());
+		}
+		let query = format!(
+			"query({}) {{\n{}\n}}",
+			parameters.join(", "),
+			fields.join("\n")
+		);
+		let mut data = graphql(&gh, &query, variables.into()).await?;
+		for i in 0..batch.len() {
+			let repo: GraphQLRepository =
+				serde_json::from_value(data[format!("r{i}")].take())?;
+			let role = if repo.owner.login == current_user {
+				ProjectRole::Owner
+			} else {
+				ProjectRole::Contributor
+			};
+			let mut p = repo.into_project(role);
+			if role == ProjectRole::Contributor {
+				// Not available from the GraphQL API
+				let owner = p.owner.clone().unwrap_or_default();
+				if let Some((commits, additions, deletions)) =
+					contributor_stats(&gh, &owner, &p.name, &current_user).await?
+				{
+					p.commits = Some(commits);
+					p.additions = Some(additions);
+					p.deletions = Some(deletions);
+				}
+			}
+			ret.push(p);
+		}
+	}
+	Ok(ret)
 }
@@ -0,0 +1,152 @@
+//! Import of projects from GitLab, either gitlab.com or a self-hosted instance.
+use crate::forge::{get_all_pages, get_json, path_segment};
+use crate::{LanguageStat, Project, ProjectRole};
+use ::serde::Deserialize;
+use ::std::collections::HashMap;
+use anyhow::Error;
+use futures::stream::TryStreamExt;
+
+pub(crate) const DEFAULT_BASE_URL: &str = "https://gitlab.com";
+
+#[derive(Deserialize)]
+struct Namespace {
+	full_path: String,
+}
+
+#[derive(Deserialize)]
+struct GitLabProject {
+	id: u64,
+	name: String,
+	description: Option<String>,
+	web_url: String,
+	#[serde(default)]
+	star_count: u64,
+	#[serde(default)]
+	forks_count: u64,
+	#[serde(default)]
+	archived: bool,
+	#[serde(default)]
+	topics: Vec<String>,
+	/// Older versions of GitLab call the topics tags
+	#[serde(default)]
+	tag_list: Vec<String>,
+	namespace: Namespace,
+	#[serde(default)]
+	forked_from_project: Option<serde_json::Value>,
+}
+
+struct Client {
+	api: String,
+	headers: Vec<(&'static str, String)>,
+}
+
+impl Client {
+	fn new(base_url: &str, token: Option<String>) -> Self {
+		let token = token.or_else(|| std::env::var("GITLAB_TOKEN").ok());
+		Self {
+			api: format!("{}/api/v4", base_url.trim_end_matches('/')),
+			headers: token
+				.into_iter()
+				.map(|token| ("PRIVATE-TOKEN", token))
+				.collect(),
+		}
+	}
+
+	async fn project(&self, p: GitLabProject, role: ProjectRole) -> Result<Project, Error> {
+		// Percentages of each language
+		let languages: HashMap<String, f64> = get_json(
+			&format!("{}/projects/{}/languages", self.api, p.id),
+			&self.headers,
+		)
+		.await?;
+		let mut languages: Vec<_> = languages
+			.into_iter()
+			.map(|(language, percentage)| LanguageStat {
+				language,
+				percentage: percentage.into(),
+			})
+			.collect();
+		languages.sort_unstable_by_key(|v| ::std::cmp::Reverse(v.percentage));
+		Ok(Project {
+			name: p.name,
+			description: p.description.filter(|d| !d.is_empty()),
+			contributions: None,
+			url: p.web_url.parse().ok(),
+			stars: Some(p.star_count),
+			forks: Some(p.forks_count),
+			active: Some(!p.archived),
+			owner: Some(p.namespace.full_path),
+			commits: None,
+			additions: None,
+			deletions: None,
+			languages,
+			tags: if p.topics.is_empty() {
+				p.tag_list
+			} else {
+				p.topics
+			},
+			role: Some(role),
+		})
+	}
+}
+
+/// Get the projects owned by `user`.
+pub(crate) async fn get_user_projects_from_gitlab(
+	base_url: &str,
+	user: &str,
+	ignore_forks: bool,
+	token: Option<String>,
+) -> Result<Vec<Project>, Error> {
+	let client = Client::new(base_url, token);
+	let projects: Vec<GitLabProject> = get_all_pages(
+		&format!(
+			"{}/users/{}/projects?per_page=100",
+			client.api,
+			path_segment(user)
+		),
+		&client.headers,
+	)
+	.await?;
+	let st: futures::stream::FuturesUnordered<_> = projects
+		.into_iter()
+		.filter(|p| !ignore_forks || p.forked_from_project.is_none())
+		.map(|p| client.project(p, ProjectRole::Owner))
+		.collect();
+	st.try_collect().await
+}
+
+/// Get the projects named in `repos`, as `namespace/project`. Projects not under `user` are
+/// considered contributions.
+pub(crate) async fn get_projects_info_from_gitlab<I>(
+	base_url: &str,
+	repos: I,
+	user: Option<&str>,
+	token: Option<String>,
+) -> Result<Vec<Project>, Error>
+where
+	I: IntoIterator,
+	<I as IntoIterator>::Item: AsRef<str>,
+{
+	let client = Client::new(base_url, token);
+	let client = &client;
+	let st: futures::stream::FuturesUnordered<_> = repos
+		.into_iter()
+		.map(|repo| {
+			let path = repo.as_ref().to_owned();
+			async move {
+				let p: GitLabProject = get_json(
+					&format!("{}/projects/{}", client.api, path_segment(&path)),
+					&client.headers,
+				)
+				.await?;
+				let role = if Some(p.namespace.full_path.as_str()) == user {
+					ProjectRole::Owner
+				} else {
+					ProjectRole::Contributor
+				};
+				client.project(p, role).await
+			}
+		})
+		.collect();
+	st.try_collect().await
+}
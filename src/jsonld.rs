@@ -0,0 +1,70 @@
+//! schema.org metadata about the person, embedded in the HTML output as JSON-LD.
+use crate::ResumeParams;
+use ::serde_json::{json, Map, Value};
+use anyhow::Error;
+
+fn organization(type_: &str, name: &str) -> Value {
+	json!({ "@type": type_, "name": name })
+}
+
+/// Describe the person the resume is about, as a schema.org `Person`.
+pub(crate) fn person(r: &ResumeParams<'_>) -> Result<String, Error> {
+	let mut person = Map::new();
+	person.insert("@context".into(), "https://schema.org".into());
+	person.insert("@type".into(), "Person".into());
+	person.insert("name".into(), r.name.into());
+	if let Some(url) = r.resume_url {
+		person.insert("url".into(), url.into());
+	}
+
+	let mut same_as = Vec::new();
+	for link in r.contacts.iter().filter_map(|c| c.link.as_deref()) {
+		if let Some(email) = link.strip_prefix("mailto:") {
+			person.entry("email").or_insert_with(|| email.into());
+		} else if let Some(phone) = link.strip_prefix("tel:") {
+			person.entry("telephone").or_insert_with(|| phone.into());
+		} else if link.starts_with("http://") || link.starts_with("https://") {
+			same_as.push(Value::from(link));
+		}
+	}
+	if !same_as.is_empty() {
+		person.insert("sameAs".into(), same_as.into());
+	}
+
+	let alumni_of: Vec<_> = r
+		.educations
+		.iter()
+		.map(|e| organization("EducationalOrganization", &e.institution))
+		.collect();
+	if !alumni_of.is_empty() {
+		person.insert("alumniOf".into(), alumni_of.into());
+	}
+	let current: Vec<_> = r
+		.experiences
+		.iter()
+		.filter(|e| e.duration.end.is_none())
+		.collect();
+	if !current.is_empty() {
+		let works_for: Vec<_> = current
+			.iter()
+			.map(|e| organization("Organization", &e.company))
+			.collect();
+		let job_titles: Vec<_> = current
+			.iter()
+			.map(|e| Value::from(e.position.as_str()))
+			.collect();
+		person.insert("worksFor".into(), works_for.into());
+		person.insert("jobTitle".into(), job_titles.into());
+	}
+
+	let knows_about: Vec<_> = r
+		.skills
+		.iter()
+		.map(|s| Value::from(s.category.as_str()))
+		.collect();
+	if !knows_about.is_empty() {
+		person.insert("knowsAbout".into(), knows_about.into());
+	}
+	// The JSON is put inside a <script> tag, so it must not contain "</"
+	Ok(serde_json::to_string_pretty(&person)?.replace("</", "<\\/"))
+}
@@ -0,0 +1,467 @@
+//! Conversion from and to the [JSON Resume](https://jsonresume.org/schema/) format.
+use crate::{
+	contact, Citation, Contact, DateRange, Degree, Education, Experience, Person, Project,
+	ProjectParam, ProjectRole, Publication as BrieflyPublication, Skill,
+};
+use ::log::*;
+use ::pulldown_cmark::escape::{escape_href, escape_html};
+use ::serde::{Deserialize, Serialize};
+use ::std::fmt::Write;
+use anyhow::Error;
+use chrono::naive::NaiveDate as Date;
+
+#[serde_with::skip_serializing_none]
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default, rename_all = "camelCase")]
+struct Resume {
+	basics: Basics,
+	#[serde(skip_serializing_if = "Vec::is_empty")]
+	work: Vec<Work>,
+	#[serde(skip_serializing_if = "Vec::is_empty")]
+	education: Vec<JsonEducation>,
+	#[serde(skip_serializing_if = "Vec::is_empty")]
+	projects: Vec<JsonProject>,
+	#[serde(skip_serializing_if = "Vec::is_empty")]
+	skills: Vec<JsonSkill>,
+	#[serde(skip_serializing_if = "Vec::is_empty")]
+	publications: Vec<Publication>,
+}
+
+#[serde_with::skip_serializing_none]
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default, rename_all = "camelCase")]
+struct Basics {
+	name: String,
+	email: Option<String>,
+	phone: Option<String>,
+	url: Option<String>,
+	#[serde(skip_serializing_if = "Vec::is_empty")]
+	profiles: Vec<Profile>,
+}
+
+#[serde_with::skip_serializing_none]
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default, rename_all = "camelCase")]
+struct Profile {
+	network: String,
+	username: Option<String>,
+	url: Option<String>,
+}
+
+#[serde_with::skip_serializing_none]
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default, rename_all = "camelCase")]
+struct Work {
+	name: Option<String>,
+	/// Older versions of the schema call it `company`
+	company: Option<String>,
+	position: String,
+	location: Option<String>,
+	start_date: Option<String>,
+	end_date: Option<String>,
+	summary: Option<String>,
+	#[serde(skip_serializing_if = "Vec::is_empty")]
+	highlights: Vec<String>,
+	#[serde(skip_serializing_if = "Vec::is_empty")]
+	keywords: Vec<String>,
+}
+
+#[serde_with::skip_serializing_none]
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default, rename_all = "camelCase")]
+struct JsonEducation {
+	institution: String,
+	area: String,
+	study_type: String,
+	start_date: Option<String>,
+	end_date: Option<String>,
+	score: Option<String>,
+	location: Option<String>,
+	#[serde(skip_serializing_if = "Vec::is_empty")]
+	courses: Vec<String>,
+}
+
+#[serde_with::skip_serializing_none]
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default, rename_all = "camelCase")]
+struct JsonProject {
+	name: String,
+	description: Option<String>,
+	#[serde(skip_serializing_if = "Vec::is_empty")]
+	highlights: Vec<String>,
+	#[serde(skip_serializing_if = "Vec::is_empty")]
+	keywords: Vec<String>,
+	url: Option<String>,
+	entity: Option<String>,
+	#[serde(skip_serializing_if = "Vec::is_empty")]
+	roles: Vec<String>,
+}
+
+#[serde_with::skip_serializing_none]
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default, rename_all = "camelCase")]
+struct JsonSkill {
+	name: String,
+	level: Option<String>,
+	#[serde(skip_serializing_if = "Vec::is_empty")]
+	keywords: Vec<String>,
+}
+
+#[serde_with::skip_serializing_none]
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default, rename_all = "camelCase")]
+struct Publication {
+	name: String,
+	publisher: Option<String>,
+	release_date: Option<String>,
+	url: Option<String>,
+	summary: Option<String>,
+}
+
+/// Parse a JSON Resume date, which can be `YYYY-MM-DD`, `YYYY-MM` or `YYYY`.
+fn parse_date(s: &str) -> Result<Date, Error> {
+	let mut parts = s.splitn(3, '-');
+	let year = parts.next().unwrap_or_default().parse()?;
+	let month = parts.next().map(str::parse).transpose()?.unwrap_or(1);
+	Date::from_ymd_opt(year, month, 1).ok_or_else(|| anyhow::anyhow!("Invalid date {}", s))
+}
+
+fn parse_duration(start: Option<&str>, end: Option<&str>) -> Result<DateRange, Error> {
+	let start = start.ok_or_else(|| anyhow::anyhow!("Missing start date"))?;
+	Ok(DateRange {
+		start: parse_date(start)?,
+		end: end.filter(|e| !e.is_empty()).map(parse_date).transpose()?,
+	})
+}
+
+fn parse_degree(study_type: &str) -> Degree {
+	let lower = study_type.to_lowercase();
+	if lower.starts_with("bachelor") || lower == "bs" || lower == "bsc" || lower == "ba" {
+		Degree::BS
+	} else if lower.starts_with("master") || lower == "ms" || lower == "msc" || lower == "ma" {
+		Degree::MS
+	} else if lower.starts_with("doctor") || lower == "phd" || lower == "ph.d." {
+		Degree::PhD
+	} else {
+		Degree::Other(study_type.to_owned())
+	}
+}
+
+/// Join a summary paragraph and a list of highlights into one markdown document.
+fn summary_markdown(summary: Option<&str>, highlights: &[String]) -> String {
+	let mut ret = summary.unwrap_or_default().trim().to_owned();
+	if !highlights.is_empty() {
+		if !ret.is_empty() {
+			ret.push_str("\n\n");
+		}
+		for h in highlights {
+			writeln!(&mut ret, "- {}", h).unwrap();
+		}
+	}
+	ret
+}
+
+fn contacts(basics: &Basics) -> Vec<Contact> {
+	let mut ret = Vec::new();
+	let mut push = |type_: &str, value: &str| {
+		ret.push(Contact {
+			type_: type_.to_owned(),
+			value: value.to_owned(),
+			web_url: None,
+		})
+	};
+	if let Some(email) = &basics.email {
+		push("email", email);
+	}
+	if let Some(phone) = &basics.phone {
+		push("phone", phone);
+	}
+	if let Some(url) = &basics.url {
+		push("blog", url);
+	}
+	for p in &basics.profiles {
+		let type_ = p.network.to_lowercase().replace(' ', "_");
+		match (p.username.as_ref(), p.url.as_ref()) {
+			(Some(username), _) => push(&type_, username),
+			(None, Some(url)) => push(&type_, url),
+			(None, None) => warn!("Ignoring {} profile without username or url", p.network),
+		}
+	}
+	ret
+}
+
+fn publication(p: &Publication) -> BrieflyPublication {
+	let mut text = "<b>".to_owned();
+	escape_html(&mut text, &p.name).unwrap();
+	text.push_str(".</b> ");
+	if let Some(publisher) = &p.publisher {
+		text.push_str("In <i>");
+		escape_html(&mut text, publisher).unwrap();
+		text.push_str("</i>. ");
+	}
+	if let Some(url) = &p.url {
+		text.push_str(r#"<a href=""#);
+		escape_href(&mut text, url).unwrap();
+		text.push_str(r#"">"#);
+		escape_html(&mut text, url).unwrap();
+		text.push_str("</a>");
+	}
+	if let Some(summary) = &p.summary {
+		debug!("Dropping summary of publication {}: {}", p.name, summary);
+	}
+	BrieflyPublication::Plain(Citation::RawWithYear {
+		text: text.trim_end().to_owned(),
+		year: p
+			.release_date
+			.as_deref()
+			.and_then(|d| d.split('-').next())
+			.and_then(|y| y.parse().ok()),
+	})
+}
+
+/// Convert a JSON Resume document into a [`Person`].
+pub(crate) fn import(data: &[u8]) -> Result<Person, Error> {
+	let r: Resume = serde_json::from_slice(data)?;
+	let experiences = r
+		.work
+		.iter()
+		.filter_map(|w| {
+			let company = w
+				.name
+				.clone()
+				.or_else(|| w.company.clone())
+				.unwrap_or_default();
+			let duration = match parse_duration(w.start_date.as_deref(), w.end_date.as_deref())
+			{
+				Ok(duration) => duration,
+				Err(e) => {
+					warn!("Skipping work at {}: {}", company, e);
+					return None;
+				}
+			};
+			Some(Experience {
+				duration,
+				company,
+				position: w.position.clone(),
+				description: summary_markdown(w.summary.as_deref(), &w.highlights),
+				location: w.location.clone(),
+				tags: w.keywords.clone(),
+			})
+		})
+		.collect();
+	let educations = r
+		.education
+		.iter()
+		.filter_map(|e| {
+			let duration = match parse_duration(e.start_date.as_deref(), e.end_date.as_deref())
+			{
+				Ok(duration) => duration,
+				Err(err) => {
+					warn!("Skipping education at {}: {}", e.institution, err);
+					return None;
+				}
+			};
+			Some(Education {
+				institution: e.institution.clone(),
+				degree: parse_degree(&e.study_type),
+				major: e.area.clone(),
+				duration,
+				location: e.location.clone(),
+				gpa: e.score.as_deref().and_then(|s| s.parse().ok()),
+				courses: if e.courses.is_empty() {
+					None
+				} else {
+					Some(e.courses.clone())
+				},
+			})
+		})
+		.collect();
+	let projects = r
+		.projects
+		.iter()
+		.filter_map(|p| {
+			let url = match p.url.as_deref().map(str::parse).transpose() {
+				Ok(url) => url,
+				Err(e) => {
+					warn!("Skipping project {}: {}", p.name, e);
+					return None;
+				}
+			};
+			Some(ProjectParam::Raw(Project {
+				name: p.name.clone(),
+				description: p.description.clone(),
+				contributions: if p.highlights.is_empty() {
+					None
+				} else {
+					Some(summary_markdown(None, &p.highlights))
+				},
+				url,
+				stars: None,
+				forks: None,
+				active: None,
+				owner: None,
+				commits: None,
+				additions: None,
+				deletions: None,
+				languages: Vec::new(),
+				tags: p.keywords.clone(),
+				// JSON Resume doesn't say whose project it is, so assume the user's own
+				role: Some(ProjectRole::Owner),
+			}))
+		})
+		.collect();
+	let skills = r
+		.skills
+		.iter()
+		.map(|s| Skill {
+			category: s.name.clone(),
+			description: match (s.level.as_deref(), s.keywords.is_empty()) {
+				(_, false) => Some(s.keywords.join(", ")),
+				(Some(level), true) => Some(level.to_owned()),
+				(None, true) => None,
+			},
+			tags: Vec::new(),
+		})
+		.collect();
+	Ok(Person {
+		name: r.basics.name.clone(),
+		resume_url: None,
+		contacts: contacts(&r.basics),
+		contact_kinds: Default::default(),
+		educations,
+		experiences,
+		projects,
+		skills,
+		references: Default::default(),
+		publications: r.publications.iter().map(publication).collect(),
+		layout: Default::default(),
+		variants: Default::default(),
+	})
+}
+
+fn format_date(d: &Date) -> String {
+	d.format("%Y-%m").to_string()
+}
+
+fn project(p: &Project) -> JsonProject {
+	let mut highlights = Vec::new();
+	if let Some(stars) = p.stars {
+		highlights.push(format!("{} stars", stars));
+	}
+	if let Some(forks) = p.forks {
+		highlights.push(format!("{} forks", forks));
+	}
+	if p.active == Some(false) {
+		highlights.push("Archived".to_owned());
+	}
+	if let Some(contributions) = &p.contributions {
+		highlights.push(contributions.trim().to_owned());
+	}
+	let mut keywords = p.tags.clone();
+	for l in &p.languages {
+		if !keywords.iter().any(|k| k.eq_ignore_ascii_case(&l.language)) {
+			keywords.push(l.language.clone());
+		}
+	}
+	JsonProject {
+		name: p.name.clone(),
+		description: p.description.clone(),
+		highlights,
+		keywords,
+		url: p.url.as_ref().map(ToString::to_string),
+		entity: p.owner.clone(),
+		roles: p.role.iter().map(ToString::to_string).collect(),
+	}
+}
+
+/// Convert a fetched [`Person`] into a JSON Resume document.
+pub(crate) fn export(p: &Person) -> Result<String, Error> {
+	let mut basics = Basics {
+		name: p.name.clone(),
+		..Default::default()
+	};
+	let kinds = contact::Registry::new(&p.contact_kinds);
+	for c in &p.contacts {
+		let c = kinds.resolve(c);
+		match c.type_.as_str() {
+			"email" => basics.email = Some(c.value),
+			"phone" => basics.phone = Some(c.value),
+			"blog" => basics.url = Some(c.value),
+			type_ => basics.profiles.push(Profile {
+				network: contact::title(type_).unwrap_or(type_).to_owned(),
+				username: Some(c.value),
+				url: c.link,
+			}),
+		}
+	}
+	let r = Resume {
+		basics,
+		work: p
+			.experiences
+			.iter()
+			.map(|e| Work {
+				name: Some(e.company.clone()),
+				company: None,
+				position: e.position.clone(),
+				location: e.location.clone(),
+				start_date: Some(format_date(&e.duration.start)),
+				end_date: e.duration.end.as_ref().map(format_date),
+				summary: Some(e.description.trim().to_owned()),
+				highlights: Vec::new(),
+				keywords: e.tags.clone(),
+			})
+			.collect(),
+		education: p
+			.educations
+			.iter()
+			.map(|e| JsonEducation {
+				institution: e.institution.clone(),
+				area: e.major.clone(),
+				study_type: e.degree.to_resume_string(),
+				start_date: Some(format_date(&e.duration.start)),
+				end_date: e.duration.end.as_ref().map(format_date),
+				score: e.gpa.map(|g| g.to_string()),
+				location: e.location.clone(),
+				courses: e.courses.clone().unwrap_or_default(),
+			})
+			.collect(),
+		projects: p
+			.projects
+			.iter()
+			.filter_map(|v| match v {
+				ProjectParam::Raw(p) => Some(project(p)),
+				_ => None,
+			})
+			.collect(),
+		skills: p
+			.skills
+			.iter()
+			.map(|s| JsonSkill {
+				name: s.category.clone(),
+				level: None,
+				keywords: s
+					.description
+					.iter()
+					.flat_map(|d| d.split(','))
+					.map(|k| k.trim().to_owned())
+					.filter(|k| !k.is_empty())
+					.collect(),
+			})
+			.collect(),
+		publications: p
+			.publications
+			.iter()
+			.filter_map(|c| match c.citation().to_raw_with_year()? {
+				Citation::RawWithYear { text, year } => Some(Publication {
+					name: crate::citation::plain_text(&text).trim().to_owned(),
+					publisher: None,
+					release_date: year.map(|y| y.to_string()),
+					url: crate::citation::first_link(&text),
+					summary: None,
+				}),
+				_ => None,
+			})
+			.collect(),
+	};
+	Ok(serde_json::to_string_pretty(&r)?)
+}
@@ -0,0 +1,318 @@
+//! LaTeX output, for places that want the resume as a `.tex` file.
+//!
+//! The document only uses the `article` class and a few common packages, so it builds with any
+//! TeX distribution. References are turned into footnotes, so there is no references section.
+use crate::md::rich::{self, Block, Inline};
+use crate::{render, Project, ProjectRole, ResumeParams, Section};
+use ::log::warn;
+use ::std::collections::HashMap;
+use ::std::fmt::Write;
+use anyhow::Error;
+
+const PREAMBLE: &str = r"\documentclass[10pt,a4paper]{article}
+\usepackage[T1]{fontenc}
+\usepackage[utf8]{inputenc}
+\usepackage[margin=1.8cm]{geometry}
+\usepackage[hidelinks]{hyperref}
+\usepackage{enumitem}
+\usepackage{titlesec}
+\usepackage{xcolor}
+
+\pagestyle{empty}
+\setlength{\parindent}{0pt}
+\setlist{nosep,leftmargin=1.5em}
+\titleformat{\section}{\large\bfseries}{}{0pt}{}[\titlerule]
+\titleformat{\subsection}{\normalsize\bfseries}{}{0pt}{}
+\titlespacing*{\section}{0pt}{1.2em}{0.6em}
+\titlespacing*{\subsection}{0pt}{0.8em}{0.3em}
+
+% \entry{title}{date}{subtitle}
+\newcommand{\entry}[3]{%
+  \par\vspace{0.5em}\noindent\textbf{#1}\hfill{\small\color{gray}#2}%
+  \if\relax\detokenize{#3}\relax\else\\\emph{#3}\fi\par\nopagebreak}
+";
+
+/// Escape text so it is typeset literally.
+fn escape(s: &str) -> String {
+	let mut ret = String::with_capacity(s.len());
+	for c in s.chars() {
+		match c {
+			'\\' => ret.push_str(r"\textbackslash{}"),
+			'~' => ret.push_str(r"\textasciitilde{}"),
+			'^' => ret.push_str(r"\textasciicircum{}"),
+			'&' | '%' | '$' | '#' | '_' | '{' | '}' => {
+				ret.push('\\');
+				ret.push(c);
+			}
+			'\u{a0}' => ret.push('~'),
+			_ => ret.push(c),
+		}
+	}
+	ret
+}
+
+/// Escape a URL for use in `\href`.
+fn escape_url(s: &str) -> String {
+	let mut ret = String::with_capacity(s.len());
+	for c in s.chars() {
+		if matches!(c, '\\' | '#' | '%' | '{' | '}') {
+			ret.push('\\');
+		}
+		ret.push(c);
+	}
+	ret
+}
+
+struct Writer<'a> {
+	out: String,
+	references: HashMap<&'a str, &'a str>,
+}
+
+impl Writer<'_> {
+	fn inlines(&mut self, inlines: &[Inline]) {
+		for inline in inlines {
+			match inline {
+				Inline::Text { text, style, link } => {
+					let mut text = escape(text);
+					if style.code {
+						text = format!(r"\texttt{{{}}}", text);
+					}
+					if style.italic {
+						text = format!(r"\emph{{{}}}", text);
+					}
+					if style.bold {
+						text = format!(r"\textbf{{{}}}", text);
+					}
+					match link {
+						Some(link) => {
+							write!(self.out, r"\href{{{}}}{{{}}}", escape_url(link), text)
+								.unwrap()
+						}
+						None => self.out.push_str(&text),
+					}
+				}
+				Inline::FootnoteReference(name) => match self.references.get(name.as_str()) {
+					Some(reference) => {
+						self.out.push_str(r"\footnote{");
+						self.inlines(&rich::from_html(reference));
+						self.out.push('}');
+					}
+					None => warn!("Reference {} is not defined", name),
+				},
+				Inline::LineBreak => self.out.push_str("\\newline\n"),
+			}
+		}
+	}
+	fn blocks(&mut self, blocks: &[Block]) {
+		for block in blocks {
+			match block {
+				Block::Paragraph(inlines) => {
+					self.inlines(inlines);
+					self.out.push_str("\n\n");
+				}
+				Block::Heading(inlines) => {
+					self.out.push_str(r"\textbf{");
+					self.inlines(inlines);
+					self.out.push_str("}\\par\n");
+				}
+				Block::List { start, items } => {
+					let env = match start {
+						Some(start) => {
+							writeln!(self.out, r"\begin{{enumerate}}[start={}]", start)
+								.unwrap();
+							"enumerate"
+						}
+						None => {
+							self.out.push_str("\\begin{itemize}\n");
+							"itemize"
+						}
+					};
+					for item in items {
+						self.out.push_str(r"\item ");
+						self.blocks(item);
+					}
+					writeln!(self.out, "\\end{{{}}}\n", env).unwrap();
+				}
+				Block::Quote(blocks) => {
+					self.out.push_str("\\begin{quote}\n");
+					self.blocks(blocks);
+					self.out.push_str("\\end{quote}\n");
+				}
+				Block::Code(code) => {
+					writeln!(self.out, "\\begin{{verbatim}}\n{}\\end{{verbatim}}", code)
+						.unwrap()
+				}
+				Block::Rule => self
+					.out
+					.push_str("\\noindent\\rule{\\linewidth}{0.4pt}\n\n"),
+			}
+		}
+	}
+	fn entry(&mut self, title: &str, date: &str, subtitle: &str) {
+		writeln!(self.out, r"\entry{{{}}}{{{}}}{{{}}}", title, date, subtitle).unwrap();
+	}
+	fn project(&mut self, p: &Project) {
+		let mut title = escape(&p.name);
+		if let Some(owner) = render::project_owner(p) {
+			title = format!(r"{{\color{{gray}}{}/}}{}", escape(owner), title);
+		}
+		if let Some(url) = &p.url {
+			title = format!(r"\href{{{}}}{{{}}}", escape_url(url.as_str()), title);
+		}
+		let stats = render::project_stats(p);
+		self.entry(&title, &stats.join(r" \textperiodcentered{} "), "");
+		if p.role == Some(ProjectRole::Contributor) {
+			if let Some(contributions) = &p.contributions {
+				self.inlines(&rich::parse_inline(contributions));
+				self.out.push_str("\n\n");
+			}
+			return;
+		}
+		if !p.languages.is_empty() {
+			let languages: Vec<_> = p
+				.languages
+				.iter()
+				.take(4)
+				.map(|l| format!(r"{} {}\%", escape(&l.language), l.percentage))
+				.collect();
+			writeln!(
+				self.out,
+				"{{\\small\\color{{gray}}{}}}\\par",
+				languages.join(r" \textperiodcentered{} ")
+			)
+			.unwrap();
+		}
+		if let Some(description) = &p.description {
+			self.blocks(&rich::parse(description));
+		}
+		if !p.tags.is_empty() {
+			let tags: Vec<_> = p.tags.iter().map(|t| escape(&format!("#{}", t))).collect();
+			writeln!(
+				self.out,
+				"{{\\small\\color{{gray}}{}}}\\par",
+				tags.join(" ")
+			)
+			.unwrap();
+		}
+		if let Some(contributions) = &p.contributions {
+			self.out.push_str("\\emph{Contributions}\\par\n");
+			self.blocks(&rich::parse(contributions));
+		}
+	}
+	fn section(&mut self, r: &ResumeParams<'_>, section: Section) {
+		match section {
+			Section::Education => {
+				self.out.push_str("\\section*{Education}\n");
+				for e in r.educations {
+					let mut subtitle = format!(
+						"{}, {}",
+						escape(&e.degree.to_resume_string()),
+						escape(&e.major)
+					);
+					if let Some(location) = &e.location {
+						write!(subtitle, r" \textperiodcentered{{}} {}", escape(location))
+							.unwrap();
+					}
+					if let Some(gpa) = e.gpa {
+						write!(subtitle, r" \textperiodcentered{{}} GPA {}", gpa).unwrap();
+					}
+					self.entry(
+						&escape(&e.institution),
+						&escape(&e.duration.to_text_string()),
+						&subtitle,
+					);
+				}
+			}
+			Section::Work => {
+				self.out.push_str("\\section*{Work}\n");
+				for e in r.experiences {
+					let mut subtitle = escape(&e.position);
+					if let Some(location) = &e.location {
+						write!(subtitle, r" \textperiodcentered{{}} {}", escape(location))
+							.unwrap();
+					}
+					self.entry(
+						&escape(&e.company),
+						&escape(&e.duration.to_text_string()),
+						&subtitle,
+					);
+					self.out.push_str("\\smallskip\n");
+					self.blocks(&rich::parse(&e.description));
+				}
+			}
+			Section::OpenSource => {
+				self.out.push_str("\\section*{Open Source}\n");
+				for (role, title) in render::PROJECT_SECTIONS {
+					if r.has_projects(role) {
+						writeln!(self.out, "\\subsection*{{{}}}", title).unwrap();
+						for p in r.projects.iter().filter(|p| p.role == Some(role)) {
+							self.project(p);
+						}
+					}
+				}
+			}
+			Section::Skills => {
+				self.out.push_str("\\section*{Skills}\n");
+				for s in r.skills {
+					write!(self.out, r"\textbf{{{}:}} ", escape(&s.category)).unwrap();
+					if let Some(description) = &s.description {
+						self.inlines(&rich::parse_inline(description));
+					}
+					self.out.push_str("\\par\n");
+				}
+			}
+			Section::Publications => {
+				self.out
+					.push_str("\\section*{Publications}\n\\begin{description}\n");
+				for (text, year) in &r.publications {
+					match year {
+						Some(year) => write!(self.out, r"\item[{}] ", year).unwrap(),
+						None => self.out.push_str(r"\item[] "),
+					}
+					self.inlines(&rich::emphasize(rich::from_html(text), r.name));
+					self.out.push('\n');
+				}
+				self.out.push_str("\\end{description}\n");
+			}
+			// References are typeset as footnotes
+			Section::References => (),
+		}
+	}
+}
+
+/// Render the resume as a LaTeX document.
+pub(crate) fn render(r: &ResumeParams<'_>) -> Result<String, Error> {
+	let mut w = Writer {
+		out: String::from(PREAMBLE),
+		references: r.references.iter().copied().collect(),
+	};
+	writeln!(
+		w.out,
+		"\\hypersetup{{pdftitle={{{0}}},pdfauthor={{{0}}}}}\n",
+		escape(r.name)
+	)?;
+	w.out.push_str("\\begin{document}\n\n");
+	writeln!(
+		w.out,
+		"{{\\LARGE\\bfseries {}}}\\par\\medskip",
+		escape(r.name)
+	)?;
+	let contacts: Vec<_> = r
+		.contacts
+		.iter()
+		.map(|c| match &c.link {
+			Some(link) => format!(r"\href{{{}}}{{{}}}", escape_url(link), escape(&c.value)),
+			None => escape(&c.value),
+		})
+		.collect();
+	writeln!(
+		w.out,
+		"{{\\small {}}}\\par\n",
+		contacts.join(r" \enspace\textperiodcentered\enspace{} ")
+	)?;
+	for section in &r.sections {
+		w.section(r, *section);
+	}
+	w.out.push_str("\n\\end{document}\n");
+	Ok(w.out)
+}
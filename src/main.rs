@@ -1,15 +1,33 @@
 #![allow(clippy::single_match)]
 use ::std::collections::HashMap;
+use ::std::io::Write as _;
+use ::std::path::{Path, PathBuf};
 use ::std::sync::Mutex;
 use anyhow::Error;
 use chrono::naive::NaiveDate as Date;
 use derive_more::Display;
 use log::*;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
+mod assets;
 mod citation;
+mod contact;
+mod docx;
 mod filters;
+mod forge;
+mod git;
+mod gitea;
 mod github;
+mod gitlab;
+mod jsonld;
+mod jsonresume;
+mod latex;
+mod markdown;
 mod md;
+mod pdf;
+mod render;
+mod template;
+mod text;
+mod vcard;
 
 lazy_static::lazy_static! {
 	static ref FOOTNOTES: Mutex<Option<HashMap<String, usize>>> = Mutex::new(Some(HashMap::new()));
@@ -32,6 +50,17 @@ impl DateRange {
 			format!("{} - Current", self.start.format("%b,&nbsp;%Y"))
 		}
 	}
+	/// Like `to_resume_string`, but for output formats other than HTML.
+	fn to_text_string(&self) -> String {
+		match self.end {
+			Some(end) => format!(
+				"{} \u{2013} {}",
+				self.start.format("%b %Y"),
+				end.format("%b %Y")
+			),
+			None => format!("{} \u{2013} Current", self.start.format("%b %Y")),
+		}
+	}
 }
 
 impl std::fmt::Display for DateRange {
@@ -54,11 +83,11 @@ impl std::str::FromStr for DateRange {
 			))
 		} else {
 			Ok(DateRange {
-				start: Date::parse_from_str(&format!("{}-01", p[0]), "%Y-%m-%d").unwrap(),
+				start: Date::parse_from_str(&format!("{}-01", p[0]), "%Y-%m-%d")?,
 				end: if p[1].is_empty() {
 					None
 				} else {
-					Some(Date::parse_from_str(&format!("{}-01", p[1]), "%Y-%m-%d").unwrap())
+					Some(Date::parse_from_str(&format!("{}-01", p[1]), "%Y-%m-%d")?)
 				},
 			})
 		}
@@ -125,11 +154,53 @@ impl Citation {
 	}
 }
 
+/// A publication, optionally tagged so variants can select it.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(untagged)]
+enum Publication {
+	Tagged {
+		tags: Vec<String>,
+		#[serde(flatten)]
+		citation: Citation,
+	},
+	Plain(Citation),
+}
+
+impl Publication {
+	fn citation(&self) -> &Citation {
+		match self {
+			Self::Tagged { citation, .. } | Self::Plain(citation) => citation,
+		}
+	}
+	fn citation_mut(&mut self) -> &mut Citation {
+		match self {
+			Self::Tagged { citation, .. } | Self::Plain(citation) => citation,
+		}
+	}
+	fn tags(&self) -> &[String] {
+		match self {
+			Self::Tagged { tags, .. } => tags,
+			Self::Plain(_) => &[],
+		}
+	}
+	fn to_raw_with_year(&self) -> Option<Publication> {
+		let citation = self.citation().to_raw_with_year()?;
+		Some(match self {
+			Self::Tagged { tags, .. } => Self::Tagged {
+				tags: tags.clone(),
+				citation,
+			},
+			Self::Plain(_) => Self::Plain(citation),
+		})
+	}
+}
+
 #[derive(Serialize, Deserialize)]
 enum Degree {
 	BS,
 	MS,
 	PhD,
+	Other(String),
 }
 
 impl Degree {
@@ -138,6 +209,7 @@ impl Degree {
 			Self::BS => "Bachelor of Science".into(),
 			Self::MS => "Master of Science".into(),
 			Self::PhD => "PhD".into(),
+			Self::Other(s) => s.clone(),
 		}
 	}
 }
@@ -173,6 +245,20 @@ struct Contact {
 	#[serde(rename = "type")]
 	type_: String,
 	value: String,
+	/// Where the service is hosted, for self-hosted ones like GitHub Enterprise or GitLab
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	web_url: Option<String>,
+}
+
+/// A contact type defined by the user.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct ContactKind {
+	/// Where the contact links to, `{value}` is replaced by the value of the contact
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	url: Option<String>,
+	/// Path to the icon, relative to the resume or the template directory
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	icon: Option<String>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -180,6 +266,49 @@ struct Skill {
 	category: String,
 	#[serde(default)]
 	description: Option<String>,
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	tags: Vec<String>,
+}
+
+/// A section of the resume, in the order they are laid out.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+enum Section {
+	Education,
+	Work,
+	OpenSource,
+	Skills,
+	Publications,
+	References,
+}
+
+impl Section {
+	fn all() -> Vec<Section> {
+		use Section::*;
+		vec![
+			Education,
+			Work,
+			OpenSource,
+			Skills,
+			Publications,
+			References,
+		]
+	}
+}
+
+#[derive(Serialize, Deserialize)]
+struct Layout {
+	/// Which sections to show, and in what order
+	#[serde(default = "Section::all")]
+	sections: Vec<Section>,
+}
+
+impl Default for Layout {
+	fn default() -> Self {
+		Self {
+			sections: Section::all(),
+		}
+	}
 }
 
 #[derive(Serialize, Deserialize)]
@@ -188,6 +317,9 @@ struct Person {
 	#[serde(default)]
 	resume_url: Option<String>,
 	contacts: Vec<Contact>,
+	/// Contact types in addition to the built-in ones
+	#[serde(default, skip_serializing_if = "HashMap::is_empty")]
+	contact_kinds: HashMap<String, ContactKind>,
 	educations: Vec<Education>,
 	experiences: Vec<Experience>,
 	projects: Vec<ProjectParam>,
@@ -196,7 +328,57 @@ struct Person {
 	#[serde(default)]
 	references: HashMap<String, Citation>,
 	#[serde(default)]
-	publications: Vec<Citation>,
+	publications: Vec<Publication>,
+	#[serde(default)]
+	layout: Layout,
+	#[serde(default, skip_serializing_if = "HashMap::is_empty")]
+	variants: HashMap<String, Variant>,
+}
+
+/// A selection of entries by their tags.
+///
+/// Entries without any tags are always included.
+#[derive(Serialize, Deserialize, Default)]
+struct Variant {
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	include_tags: Vec<String>,
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	exclude_tags: Vec<String>,
+}
+
+impl Variant {
+	fn matches(&self, tags: &[String]) -> bool {
+		if tags.is_empty() {
+			return true;
+		}
+		if tags.iter().any(|t| self.exclude_tags.contains(t)) {
+			return false;
+		}
+		self.include_tags.is_empty() || tags.iter().any(|t| self.include_tags.contains(t))
+	}
+}
+
+impl Person {
+	/// Remove the entries not selected by the variant `name`.
+	fn select_variant(&mut self, name: &str) -> Result<(), Error> {
+		let Some(variant) = self.variants.get(name) else {
+			let mut names: Vec<_> = self.variants.keys().map(String::as_str).collect();
+			names.sort_unstable();
+			return Err(anyhow::anyhow!(
+				"Unknown variant {}, available variants: {}",
+				name,
+				names.join(", ")
+			));
+		};
+		self.experiences.retain(|e| variant.matches(&e.tags));
+		self.projects.retain(|p| match p {
+			ProjectParam::Raw(p) => variant.matches(&p.tags),
+			_ => true,
+		});
+		self.skills.retain(|s| variant.matches(&s.tags));
+		self.publications.retain(|p| variant.matches(p.tags()));
+		Ok(())
+	}
 }
 
 #[allow(clippy::large_enum_variant)]
@@ -209,19 +391,14 @@ enum ProjectParam {
 	Raw(Project),
 }
 
-#[derive(Serialize, Deserialize, Copy, Clone, PartialEq)]
+#[derive(Serialize, Deserialize, Copy, Clone, PartialEq, Default)]
 #[serde(rename_all = "snake_case")]
 enum ProjectImportMode {
 	Whitelist,
+	#[default]
 	Combine,
 }
 
-impl Default for ProjectImportMode {
-	fn default() -> Self {
-		Self::Combine
-	}
-}
-
 #[derive(Serialize, Deserialize, Copy, Clone)]
 #[serde(rename_all = "snake_case")]
 enum ProjectSortOrder {
@@ -236,6 +413,50 @@ enum ProjectSortOrder {
 #[serde(tag = "from", rename_all = "lowercase")]
 enum ProjectImport {
 	GitHub {
+		/// The API of a GitHub Enterprise Server, like `https://github.example.com/api/v3`;
+		/// api.github.com by default
+		#[serde(default)]
+		base_url: Option<String>,
+		#[serde(default)]
+		ignore_forks: bool,
+		#[serde(default)]
+		repos: Option<Vec<String>>,
+		/// Import the repositories of these organizations, in addition to `repos`, instead of
+		/// all of the user's own
+		#[serde(default)]
+		organizations: Vec<String>,
+		/// Import the repositories of these teams, as `org/team`, like `organizations`
+		#[serde(default)]
+		teams: Vec<String>,
+		/// Fetch with the GraphQL API, which needs far fewer requests
+		#[serde(default)]
+		graphql: bool,
+		#[serde(default)]
+		token: Option<String>,
+	},
+	GitLab {
+		/// The GitLab instance, gitlab.com by default
+		#[serde(default)]
+		base_url: Option<String>,
+		/// Whose projects to import, defaults to the `gitlab` contact
+		#[serde(default)]
+		user: Option<String>,
+		#[serde(default)]
+		ignore_forks: bool,
+		#[serde(default)]
+		repos: Option<Vec<String>>,
+		#[serde(default)]
+		token: Option<String>,
+	},
+	#[serde(alias = "forgejo", alias = "codeberg")]
+	Gitea {
+		/// The Gitea or Forgejo instance, codeberg.org by default
+		#[serde(default)]
+		base_url: Option<String>,
+		/// Whose repositories to import, defaults to the `gitea`, `forgejo` or `codeberg`
+		/// contact, and then to the owner of the token
+		#[serde(default)]
+		user: Option<String>,
 		#[serde(default)]
 		ignore_forks: bool,
 		#[serde(default)]
@@ -243,6 +464,35 @@ enum ProjectImport {
 		#[serde(default)]
 		token: Option<String>,
 	},
+	/// Repositories the user contributed to, found from their merged pull requests
+	#[serde(rename = "github_pull_requests")]
+	GitHubPullRequests {
+		/// The API of a GitHub Enterprise Server, api.github.com by default
+		#[serde(default)]
+		base_url: Option<String>,
+		/// Defaults to the `github` contact
+		#[serde(default)]
+		user: Option<String>,
+		/// Repositories with fewer merged pull requests are left out
+		#[serde(default = "default_min_pull_requests")]
+		min_pull_requests: usize,
+		/// Repositories to leave out, as `owner/repo`
+		#[serde(default)]
+		exclude: Vec<String>,
+		#[serde(default)]
+		token: Option<String>,
+	},
+	Git {
+		/// Paths to the repositories, relative to the resume
+		repos: Vec<PathBuf>,
+		/// Only commits by these authors are counted, defaults to the email contacts
+		#[serde(default)]
+		emails: Vec<String>,
+	},
+}
+
+fn default_min_pull_requests() -> usize {
+	1
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Display)]
@@ -403,12 +653,15 @@ mod serde_option_display_fromstr {
 
 use askama::Template;
 
+#[derive(Serialize)]
 struct ContactParams {
+	#[serde(rename = "type")]
+	type_: String,
 	value: String,
 	icon: Option<String>,
 	link: Option<String>,
 }
-#[derive(Template)]
+#[derive(Template, Serialize)]
 #[template(path = "resume.html", escape = "none")]
 struct ResumeParams<'a> {
 	name: &'a str,
@@ -420,15 +673,44 @@ struct ResumeParams<'a> {
 	references: Vec<(&'a str, &'a str)>,
 	publications: Vec<(&'a str, Option<u32>)>,
 	skills: &'a [Skill],
+	/// Sections to render, with the empty ones removed
+	sections: Vec<Section>,
+	/// schema.org description of the person, as JSON-LD
+	json_ld: String,
+	/// The contacts as a vCard
+	vcard: String,
 }
 
-async fn fetch(mut person: Person) -> anyhow::Result<Person> {
+impl ResumeParams<'_> {
+	fn has_projects(&self, role: ProjectRole) -> bool {
+		self.projects.iter().any(|p| p.role == Some(role))
+	}
+}
+
+/// Fetch remote data. `dir` is the directory of the resume, which paths are relative to.
+async fn fetch(mut person: Person, dir: &Path) -> anyhow::Result<Person> {
 	use futures::stream::TryStreamExt;
 	let github_username = person
 		.contacts
 		.iter()
 		.find(|v| v.type_ == "github")
 		.map(|v| v.value.as_str());
+	let gitlab_username = person
+		.contacts
+		.iter()
+		.find(|v| v.type_ == "gitlab")
+		.map(|v| v.value.as_str());
+	let gitea_username = person
+		.contacts
+		.iter()
+		.find(|v| matches!(v.type_.as_str(), "gitea" | "forgejo" | "codeberg"))
+		.map(|v| v.value.as_str());
+	let emails: Vec<_> = person
+		.contacts
+		.iter()
+		.filter(|v| v.type_ == "email" || v.type_ == "mail")
+		.map(|v| v.value.clone())
+		.collect();
 
 	let mut project_map = HashMap::new();
 	let mut sort_order = None;
@@ -437,31 +719,175 @@ async fn fetch(mut person: Person) -> anyhow::Result<Person> {
 	for pi in person.projects.iter() {
 		match pi {
 			ProjectParam::Import(ProjectImport::GitHub {
+				base_url,
+				ignore_forks,
+				repos,
+				organizations,
+				teams,
+				graphql,
+				token,
+			}) => {
+				let base_url = base_url.as_deref().unwrap_or(github::DEFAULT_BASE_URL);
+				let mut projects = Vec::new();
+				if let Some(repos) = repos {
+					let user = github_username.map(ToOwned::to_owned);
+					projects.extend(if *graphql {
+						github::get_projects_info_from_github_graphql(
+							base_url,
+							repos,
+							token.clone(),
+							user,
+						)
+						.await?
+					} else {
+						github::get_projects_info_from_github(
+							base_url,
+							repos,
+							token.clone(),
+							user,
+						)
+						.await?
+					});
+				}
+				if !organizations.is_empty() || !teams.is_empty() {
+					projects.extend(if *graphql {
+						github::get_organization_projects_from_github_graphql(
+							base_url,
+							organizations,
+							teams,
+							*ignore_forks,
+							token.clone(),
+						)
+						.await?
+					} else {
+						github::get_organization_projects_from_github(
+							base_url,
+							organizations,
+							teams,
+							*ignore_forks,
+							token.clone(),
+						)
+						.await?
+					});
+				}
+				// Without anything else to import, import the user's own repositories
+				if repos.is_none() && organizations.is_empty() && teams.is_empty() {
+					projects.extend(if *graphql {
+						github::get_user_projects_from_github_graphql(
+							base_url,
+							*ignore_forks,
+							token.clone(),
+						)
+						.await?
+					} else {
+						github::get_user_projects_from_github(
+							base_url,
+							*ignore_forks,
+							token.clone(),
+						)
+						.await?
+					});
+				}
+				project_map.extend(projects.into_iter().map(|v| (v.name.clone(), v)));
+			}
+			ProjectParam::Import(ProjectImport::GitLab {
+				base_url,
+				user,
+				ignore_forks,
+				repos,
+				token,
+			}) => {
+				let base_url = base_url.as_deref().unwrap_or(gitlab::DEFAULT_BASE_URL);
+				let user = user.as_deref().or(gitlab_username);
+				let projects = match (repos, user) {
+					(Some(repos), user) => {
+						gitlab::get_projects_info_from_gitlab(
+							base_url,
+							repos,
+							user,
+							token.clone(),
+						)
+						.await?
+					}
+					(None, Some(user)) => {
+						gitlab::get_user_projects_from_gitlab(
+							base_url,
+							user,
+							*ignore_forks,
+							token.clone(),
+						)
+						.await?
+					}
+					(None, None) => {
+						return Err(anyhow::anyhow!(
+							"Importing projects from GitLab needs either `repos`, `user` or a \
+							 gitlab contact"
+						))
+					}
+				};
+				project_map.extend(projects.into_iter().map(|v| (v.name.clone(), v)));
+			}
+			ProjectParam::Import(ProjectImport::Gitea {
+				base_url,
+				user,
 				ignore_forks,
-				repos: None,
+				repos,
 				token,
-			}) => project_map.extend(
-				github::get_user_projects_from_github(*ignore_forks, token.clone())
+			}) => {
+				let base_url = base_url.as_deref().unwrap_or(gitea::DEFAULT_BASE_URL);
+				let user = user.as_deref().or(gitea_username);
+				let projects = if let Some(repos) = repos {
+					gitea::get_projects_info_from_gitea(base_url, repos, user, token.clone())
+						.await?
+				} else {
+					gitea::get_user_projects_from_gitea(
+						base_url,
+						user,
+						*ignore_forks,
+						token.clone(),
+					)
 					.await?
-					.into_iter()
-					.map(|v| (v.name.clone(), v)),
-			),
-			ProjectParam::Import(ProjectImport::GitHub {
-				repos: Some(repos),
+				};
+				project_map.extend(projects.into_iter().map(|v| (v.name.clone(), v)));
+			}
+			ProjectParam::Import(ProjectImport::GitHubPullRequests {
+				base_url,
+				user,
+				min_pull_requests,
+				exclude,
 				token,
-				..
 			}) => {
+				let base_url = base_url.as_deref().unwrap_or(github::DEFAULT_BASE_URL);
+				let user = user.as_deref().or(github_username);
 				project_map.extend(
-					github::get_projects_info_from_github(
-						repos,
+					github::get_contributions_from_github(
+						base_url,
+						user.map(ToOwned::to_owned),
+						*min_pull_requests,
+						exclude,
 						token.clone(),
-						github_username.map(ToOwned::to_owned),
 					)
 					.await?
 					.into_iter()
 					.map(|v| (v.name.clone(), v)),
 				);
 			}
+			ProjectParam::Import(ProjectImport::Git {
+				repos,
+				emails: authors,
+			}) => {
+				let authors = if authors.is_empty() { &emails } else { authors };
+				for repo in repos {
+					let path = dir.join(repo);
+					let authors = authors.clone();
+					let project = tokio::task::spawn_blocking(move || {
+						git::get_project_from_git(&path, &authors)
+					})
+					.await?
+					.map_err(|e| e.context(format!("Cannot read {}", repo.display())))?;
+					project_map.insert(project.name.clone(), project);
+				}
+			}
 			ProjectParam::Sort { order_by } => {
 				sort_order = Some(*order_by);
 			}
@@ -520,10 +946,10 @@ async fn fetch(mut person: Person) -> anyhow::Result<Person> {
 			.collect();
 		projects = raw_entries
 			.into_iter()
-			.filter_map(|name| project_map.get(name).map(Clone::clone))
+			.filter_map(|name| project_map.get(name).cloned())
 			.collect();
 	} else {
-		projects = project_map.iter().map(|(_, v)| v.clone()).collect();
+		projects = project_map.values().cloned().collect();
 	}
 	if let Some(sort_order) = sort_order {
 		use ::std::cmp::Reverse;
@@ -548,13 +974,13 @@ async fn fetch(mut person: Person) -> anyhow::Result<Person> {
 					.enumerate()
 					.map(|(i, v)| (v, i))
 					.collect();
-				projects.sort_unstable_by_key(|v| raw_entries.get(&v.name).map(|v| *v));
+				projects.sort_unstable_by_key(|v| raw_entries.get(&v.name).copied());
 			}
 			_ => {}
 		}
 	}
 	debug!("{}", serde_yaml::to_string(&projects)?);
-	person.projects = projects.into_iter().map(|v| ProjectParam::Raw(v)).collect();
+	person.projects = projects.into_iter().map(ProjectParam::Raw).collect();
 	person.projects.push(ProjectParam::Sort {
 		order_by: ProjectSortOrder::Manual,
 	});
@@ -566,15 +992,21 @@ async fn fetch(mut person: Person) -> anyhow::Result<Person> {
 		.references
 		.iter_mut()
 		.map(|(_, v)| v)
-		.chain(person.publications.iter_mut())
+		.chain(
+			person
+				.publications
+				.iter_mut()
+				.map(Publication::citation_mut),
+		)
 		.map(|v| {
 			async move {
-				Result::<_, Error>::Ok(match v {
+				match v {
 					Citation::Url(url) => url.fetch().await?,
 					Citation::Doi(doi) => doi.fetch().await?,
 					Citation::Bibtex(bib) => bib.fetch().await?,
 					_ => (),
-				})
+				}
+				Result::<_, Error>::Ok(())
 			}
 			.boxed()
 		})
@@ -614,27 +1046,14 @@ fn build_params<'a>(
 		references.sort_unstable_by_key(|(k, _)| footnotes.get(*k).unwrap());
 	}
 
+	let kinds = contact::Registry::new(&p.contact_kinds);
 	for i in p.contacts.iter() {
-		c.push(ContactParams {
-			link: match i.type_.as_str() {
-				"github" => Some(format!("https://github.com/{}", i.value)),
-				"email" => Some(format!("mailto:{}", i.value)),
-				"blog" => Some(i.value.clone()),
-				_ => None,
-			},
-			icon: match i.type_.as_str() {
-				"github" => Some("icons/github.svg".into()),
-				"email" => Some("icons/mail.svg".into()),
-				"blog" => Some("icons/blog.svg".into()),
-				_ => None,
-			},
-			value: i.value.clone(),
-		});
+		c.push(kinds.resolve(i));
 	}
 
-	Ok(ResumeParams {
+	let mut ret = ResumeParams {
 		name: &p.name,
-		resume_url: p.resume_url.as_ref().map(String::as_str),
+		resume_url: p.resume_url.as_deref(),
 		contacts: c,
 		educations: p.educations.as_slice(),
 		experiences: p.experiences.as_slice(),
@@ -649,52 +1068,335 @@ fn build_params<'a>(
 		publications: p
 			.publications
 			.iter()
-			.filter_map(|v| match v {
+			.filter_map(|v| match v.citation() {
 				Citation::RawWithYear { text, year } => Some((text.as_str(), *year)),
 				_ => None,
 			})
 			.collect(),
 		references,
 		skills: p.skills.as_slice(),
-	})
+		sections: Vec::new(),
+		json_ld: String::new(),
+		vcard: String::new(),
+	};
+	ret.json_ld = jsonld::person(&ret)?;
+	ret.vcard = vcard::render(&ret);
+	ret.sections = p
+		.layout
+		.sections
+		.iter()
+		.copied()
+		.filter(|s| match s {
+			Section::Education => !ret.educations.is_empty(),
+			Section::Work => !ret.experiences.is_empty(),
+			Section::OpenSource => {
+				ret.has_projects(ProjectRole::Owner)
+					|| ret.has_projects(ProjectRole::Maintainer)
+					|| ret.has_projects(ProjectRole::Contributor)
+			}
+			Section::Skills => !ret.skills.is_empty(),
+			Section::Publications => !ret.publications.is_empty(),
+			Section::References => !ret.references.is_empty(),
+		})
+		.collect();
+	Ok(ret)
 }
 
-fn main() -> Result<(), Error> {
-	env_logger::init();
-	let args = clap::Command::new("resume")
-		.arg(clap::Arg::new("input").required(true))
-		.get_matches();
-	let input_filename = args.get_one::<String>("input").unwrap();
-	let cache_filename = format!("{}-cache", input_filename);
-	let cache_info = std::fs::metadata(&cache_filename);
-	let input_info = std::fs::metadata(&input_filename)?;
-	let cache_data = if cache_info.is_ok() {
-		std::fs::read(&cache_filename).ok()
-	} else {
-		None
+fn cli() -> clap::Command {
+	use clap::{value_parser, Arg, ArgAction, Command};
+	let input = Arg::new("input")
+		.required(true)
+		.value_parser(value_parser!(PathBuf))
+		.help("The resume description, in YAML");
+	let force_refresh = Arg::new("force-refresh")
+		.long("force-refresh")
+		.action(ArgAction::SetTrue)
+		.help("Fetch remote data even if the cache is up to date");
+	Command::new("briefly")
+		.about("Write what's important about yourself, let the machine fill in the rest")
+		.subcommand_required(true)
+		.arg_required_else_help(true)
+		.subcommand(
+			Command::new("build")
+				.about("Render the resume, fetching remote data if the cache is stale")
+				.arg(input.clone())
+				.arg(
+					Arg::new("output")
+						.short('o')
+						.long("output")
+						.value_parser(value_parser!(PathBuf))
+						.help("Write the rendered resume to this file instead of stdout"),
+				)
+				.arg(
+					Arg::new("variant")
+						.long("variant")
+						.help("Only include entries selected by this variant"),
+				)
+				.arg(
+					Arg::new("format")
+						.short('f')
+						.long("format")
+						.value_parser([
+							"html",
+							"pdf",
+							"docx",
+							"latex",
+							"markdown",
+							"text",
+							"json-resume",
+							"vcard",
+						])
+						.default_value("html")
+						.help("Output format"),
+				)
+				.arg(
+					Arg::new("out-dir")
+						.long("out-dir")
+						.value_parser(value_parser!(PathBuf))
+						.conflicts_with("output")
+						.help("Write index.html and the files it needs into this directory"),
+				)
+				.arg(
+					Arg::new("template-dir")
+						.long("template-dir")
+						.value_parser(value_parser!(PathBuf))
+						.help("Render with the templates in this directory instead of the built-in one"),
+				)
+				.arg(
+					Arg::new("bundle")
+						.long("bundle")
+						.action(ArgAction::SetTrue)
+						.conflicts_with("out-dir")
+						.help("Inline stylesheets and icons, producing a single HTML file"),
+				)
+				.arg(force_refresh.conflicts_with("offline"))
+				.arg(
+					Arg::new("offline")
+						.long("offline")
+						.action(ArgAction::SetTrue)
+						.help("Never access the network, render from the cache only"),
+				),
+		)
+		.subcommand(
+			Command::new("fetch")
+				.about("Fetch remote data and store it in the cache, even if it is up to date")
+				.arg(input.clone()),
+		)
+		.subcommand(
+			Command::new("validate")
+				.about("Check that the input file is well-formed")
+				.arg(input.clone()),
+		)
+		.subcommand(
+			Command::new("import")
+				.about("Convert a JSON Resume document into briefly's YAML format")
+				.arg(
+					Arg::new("input")
+						.required(true)
+						.value_parser(value_parser!(PathBuf))
+						.help("The resume.json file to import"),
+				)
+				.arg(
+					Arg::new("output")
+						.short('o')
+						.long("output")
+						.value_parser(value_parser!(PathBuf))
+						.help("Write the YAML to this file instead of stdout"),
+				),
+		)
+		.subcommand(
+			Command::new("cache")
+				.about("Manage the cache of fetched data")
+				.subcommand_required(true)
+				.subcommand(
+					Command::new("clean")
+						.about("Remove the cache file of an input")
+						.arg(input),
+				),
+		)
+}
+
+/// How the cache of fetched data is used when loading a resume.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum CachePolicy {
+	/// Use the cache if it is newer than the input, otherwise fetch.
+	Auto,
+	/// Always fetch, and overwrite the cache.
+	Refresh,
+	/// Never fetch, the cache must exist.
+	Offline,
+}
+
+fn cache_path(input: &Path) -> PathBuf {
+	let mut cache = input.as_os_str().to_owned();
+	cache.push("-cache");
+	cache.into()
+}
+
+fn read_input(input: &Path) -> Result<Person, Error> {
+	let f = std::fs::read(input)
+		.map_err(|e| anyhow::anyhow!("Cannot read {}: {}", input.display(), e))?;
+	let r = serde_yaml::from_slice::<Person>(f.as_slice())?;
+	debug!("{}", serde_yaml::to_string(&r)?);
+	Ok(r)
+}
+
+/// Returns the cached data, and whether it is at least as new as the input.
+fn read_cache(input: &Path) -> Result<Option<(Person, bool)>, Error> {
+	let cache = cache_path(input);
+	let Ok(cache_info) = std::fs::metadata(&cache) else {
+		return Ok(None);
 	};
-	let r = if cache_data.is_some() && cache_info?.modified()? >= input_info.modified()? {
-		serde_yaml::from_slice::<Person>(cache_data.unwrap().as_slice())?
-	} else {
-		let f = std::fs::read(input_filename).unwrap();
-		let r = serde_yaml::from_slice::<Person>(f.as_slice())?;
-		debug!("{}", serde_yaml::to_string(&r)?);
-
-		let mut runtime = tokio::runtime::Runtime::new()?;
-		let r = runtime.block_on(fetch(r))?;
-		if let Some(mut cache_f) =
-			std::fs::File::create(format!("{}-cache", input_filename)).ok()
-		{
-			use ::std::io::Write;
-			write!(cache_f, "{}", serde_yaml::to_string(&r)?)?;
-		}
-		r
+	let fresh = cache_info.modified()? >= std::fs::metadata(input)?.modified()?;
+	let r = serde_yaml::from_slice::<Person>(std::fs::read(&cache)?.as_slice())?;
+	Ok(Some((r, fresh)))
+}
+
+fn fetch_and_cache(input: &Path) -> Result<Person, Error> {
+	let r = read_input(input)?;
+	let runtime = tokio::runtime::Runtime::new()?;
+	let dir = input.parent().unwrap_or_else(|| Path::new("."));
+	let r = runtime.block_on(fetch(r, dir))?;
+	let cache = cache_path(input);
+	if let Err(e) = std::fs::write(&cache, serde_yaml::to_string(&r)?) {
+		warn!("Cannot write cache {}: {}", cache.display(), e);
+	}
+	Ok(r)
+}
+
+fn load(input: &Path, policy: CachePolicy) -> Result<Person, Error> {
+	match policy {
+		CachePolicy::Refresh => fetch_and_cache(input),
+		CachePolicy::Offline => match read_cache(input)? {
+			Some((r, fresh)) => {
+				if !fresh {
+					warn!(
+						"Cache is older than {}, output may be stale",
+						input.display()
+					);
+				}
+				Ok(r)
+			}
+			None => Err(anyhow::anyhow!(
+				"No cache found for {}, run `briefly fetch` first",
+				input.display()
+			)),
+		},
+		CachePolicy::Auto => match read_cache(input)? {
+			Some((r, true)) => {
+				info!("Using cached data from {}", cache_path(input).display());
+				Ok(r)
+			}
+			_ => fetch_and_cache(input),
+		},
+	}
+}
+
+fn render_html(r: &Person, theme: Option<&template::Theme>) -> Result<String, Error> {
+	let render = |resume: ResumeParams| match theme {
+		Some(theme) => theme.render(&resume),
+		None => Ok(resume.render()?),
 	};
-	let resume = build_params(&r, None)?;
-	resume.render()?;
+	assets::take_used();
+	// Render once to collect footnote numbers, then again with unused references removed
+	render(build_params(r, None)?)?;
 
 	let footnotes = FOOTNOTES.lock().unwrap().replace(HashMap::new()).unwrap();
-	let resume = build_params(&r, Some(footnotes))?;
-	println!("{}", resume.render()?);
+	render(build_params(r, Some(footnotes))?)
+}
+
+fn main() -> Result<(), Error> {
+	env_logger::init();
+	let args = cli().get_matches();
+	match args.subcommand() {
+		Some(("build", args)) => {
+			let input = args.get_one::<PathBuf>("input").unwrap();
+			let policy = if args.get_flag("offline") {
+				CachePolicy::Offline
+			} else if args.get_flag("force-refresh") {
+				CachePolicy::Refresh
+			} else {
+				CachePolicy::Auto
+			};
+			let format = args.get_one::<String>("format").unwrap().as_str();
+			if format != "html"
+				&& (args.get_flag("bundle")
+					|| args.contains_id("out-dir")
+					|| args.contains_id("template-dir"))
+			{
+				return Err(anyhow::anyhow!(
+					"--bundle, --out-dir and --template-dir can only be used with HTML output"
+				));
+			}
+			let theme = args
+				.get_one::<PathBuf>("template-dir")
+				.map(|dir| template::Theme::load(dir))
+				.transpose()?;
+			let mut r = load(input, policy)?;
+			// Icons of custom contact kinds are relative to the resume
+			let dir = input.parent().unwrap_or_else(|| Path::new("."));
+			for icon in r.contact_kinds.values().filter_map(|k| k.icon.as_ref()) {
+				assets::add_file(icon, dir.join(icon));
+			}
+			if let Some(variant) = args.get_one::<String>("variant") {
+				r.select_variant(variant)?;
+			}
+			assets::set_inline(args.get_flag("bundle"));
+			let output = match format {
+				"json-resume" => jsonresume::export(&r)?.into_bytes(),
+				"pdf" => pdf::render(&build_params(&r, None)?)?,
+				"docx" => docx::render(&build_params(&r, None)?)?,
+				"latex" => latex::render(&build_params(&r, None)?)?.into_bytes(),
+				"markdown" => markdown::render(&build_params(&r, None)?)?.into_bytes(),
+				"text" => text::render(&build_params(&r, None)?)?.into_bytes(),
+				"vcard" => build_params(&r, None)?.vcard.into_bytes(),
+				_ => render_html(&r, theme.as_ref())?.into_bytes(),
+			};
+			if let Some(out_dir) = args.get_one::<PathBuf>("out-dir") {
+				assets::write_dir(out_dir, &output, &assets::take_used())?;
+			} else if let Some(path) = args.get_one::<PathBuf>("output") {
+				std::fs::write(path, output)?;
+			} else {
+				let mut stdout = std::io::stdout();
+				stdout.write_all(&output)?;
+				// Like the HTML always printed before the other formats were added
+				if format == "html" {
+					stdout.write_all(b"\n")?;
+				}
+			}
+		}
+		Some(("fetch", args)) => {
+			let input = args.get_one::<PathBuf>("input").unwrap();
+			load(input, CachePolicy::Refresh)?;
+		}
+		Some(("validate", args)) => {
+			let input = args.get_one::<PathBuf>("input").unwrap();
+			read_input(input)?;
+			eprintln!("{} is valid", input.display());
+		}
+		Some(("import", args)) => {
+			let input = args.get_one::<PathBuf>("input").unwrap();
+			let r = jsonresume::import(&std::fs::read(input)?)?;
+			let yaml = serde_yaml::to_string(&r)?;
+			match args.get_one::<PathBuf>("output") {
+				Some(output) => std::fs::write(output, yaml)?,
+				None => print!("{}", yaml),
+			}
+		}
+		Some(("cache", args)) => match args.subcommand() {
+			Some(("clean", args)) => {
+				let cache = cache_path(args.get_one::<PathBuf>("input").unwrap());
+				match std::fs::remove_file(&cache) {
+					Ok(()) => info!("Removed {}", cache.display()),
+					Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+						info!("{} does not exist", cache.display())
+					}
+					Err(e) => return Err(e.into()),
+				}
+			}
+			_ => unreachable!(),
+		},
+		_ => unreachable!(),
+	}
 	Ok(())
 }
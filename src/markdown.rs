@@ -0,0 +1,219 @@
+//! Markdown output, e.g. for a GitHub profile `README.md`.
+//!
+//! Descriptions are already written in markdown, so they are copied as they are. Only the
+//! citations, which are HTML, need to be converted.
+use crate::md::rich::{self, Inline};
+use crate::{render, Project, ProjectRole, ResumeParams, Section};
+use ::std::fmt::Write;
+use anyhow::Error;
+
+/// Escape characters that would otherwise be interpreted as markdown.
+pub(crate) fn escape(s: &str) -> String {
+	let mut ret = String::with_capacity(s.len());
+	for c in s.chars() {
+		if matches!(
+			c,
+			'\\' | '`' | '*' | '_' | '[' | ']' | '<' | '>' | '#' | '|'
+		) {
+			ret.push('\\');
+		}
+		ret.push(c);
+	}
+	ret
+}
+
+fn inlines(inlines: &[Inline]) -> String {
+	let mut ret = String::new();
+	for inline in inlines {
+		match inline {
+			Inline::Text { text, style, link } => {
+				let mut text = if style.code {
+					format!("`{}`", text)
+				} else {
+					escape(text)
+				};
+				if style.italic {
+					text = format!("*{}*", text);
+				}
+				if style.bold {
+					text = format!("**{}**", text);
+				}
+				match link {
+					Some(link) => write!(ret, "[{}]({})", text, link).unwrap(),
+					None => ret.push_str(&text),
+				}
+			}
+			Inline::FootnoteReference(name) => write!(ret, "[^{}]", name).unwrap(),
+			Inline::LineBreak => ret.push_str("  \n"),
+		}
+	}
+	ret
+}
+
+/// Join the lines of some markdown, so it can be used inside a list item.
+fn one_line(s: &str) -> String {
+	s.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn title(p: &Project) -> String {
+	let name = match render::project_owner(p) {
+		Some(owner) => format!("{}/{}", owner, p.name),
+		None => p.name.clone(),
+	};
+	match &p.url {
+		Some(url) => format!("[{}]({})", escape(&name), url),
+		None => escape(&name),
+	}
+}
+
+fn project(out: &mut String, p: &Project) {
+	writeln!(out, "#### {}\n", title(p)).unwrap();
+	let mut stats = render::project_stats(p);
+	let languages: Vec<_> = p
+		.languages
+		.iter()
+		.map(|l| format!("{} {}%", l.language, l.percentage))
+		.collect();
+	if !languages.is_empty() {
+		stats.push(languages.join(", "));
+	}
+	if !stats.is_empty() {
+		writeln!(out, "{}\n", stats.join(" · ")).unwrap();
+	}
+	if let Some(description) = &p.description {
+		writeln!(out, "{}\n", description.trim()).unwrap();
+	}
+	if !p.tags.is_empty() {
+		let tags: Vec<_> = p.tags.iter().map(|t| format!("`#{}`", t)).collect();
+		writeln!(out, "{}\n", tags.join(" ")).unwrap();
+	}
+	if let Some(contributions) = &p.contributions {
+		writeln!(out, "**Contributions**\n\n{}\n", contributions.trim()).unwrap();
+	}
+}
+
+/// Contributions are short, so they are list items rather than subsections.
+fn contribution(out: &mut String, p: &Project) {
+	write!(out, "- {}", title(p)).unwrap();
+	if let Some(contributions) = &p.contributions {
+		write!(out, ": {}", one_line(contributions)).unwrap();
+	}
+	out.push('\n');
+}
+
+fn section(out: &mut String, r: &ResumeParams<'_>, section: Section) {
+	match section {
+		Section::Education => {
+			out.push_str("## Education\n\n");
+			for e in r.educations {
+				write!(
+					out,
+					"**{}** — {}, {}",
+					escape(&e.institution),
+					e.degree.to_resume_string(),
+					escape(&e.major)
+				)
+				.unwrap();
+				if let Some(location) = &e.location {
+					write!(out, " · {}", escape(location)).unwrap();
+				}
+				if let Some(gpa) = e.gpa {
+					write!(out, " · GPA {}", gpa).unwrap();
+				}
+				writeln!(out, "  \n*{}*\n", e.duration.to_text_string()).unwrap();
+			}
+		}
+		Section::Work => {
+			out.push_str("## Work\n\n");
+			for e in r.experiences {
+				writeln!(
+					out,
+					"### {} — {}\n",
+					escape(&e.company),
+					escape(&e.position)
+				)
+				.unwrap();
+				write!(out, "*{}", e.duration.to_text_string()).unwrap();
+				if let Some(location) = &e.location {
+					write!(out, " · {}", escape(location)).unwrap();
+				}
+				writeln!(out, "*\n\n{}\n", e.description.trim()).unwrap();
+			}
+		}
+		Section::OpenSource => {
+			out.push_str("## Open Source\n\n");
+			for (role, heading) in render::PROJECT_SECTIONS {
+				if !r.has_projects(role) {
+					continue;
+				}
+				writeln!(out, "### {}\n", heading).unwrap();
+				for p in r.projects.iter().filter(|p| p.role == Some(role)) {
+					match role {
+						ProjectRole::Contributor => contribution(out, p),
+						_ => project(out, p),
+					}
+				}
+				// Ends the list of contributions
+				if role == ProjectRole::Contributor {
+					out.push('\n');
+				}
+			}
+		}
+		Section::Skills => {
+			out.push_str("## Skills\n\n");
+			for s in r.skills {
+				write!(out, "- **{}**", escape(&s.category)).unwrap();
+				if let Some(description) = &s.description {
+					write!(out, ": {}", one_line(description)).unwrap();
+				}
+				out.push('\n');
+			}
+			out.push('\n');
+		}
+		Section::Publications => {
+			out.push_str("## Publications\n\n");
+			for (text, year) in &r.publications {
+				out.push_str("- ");
+				if let Some(year) = year {
+					write!(out, "**{}** ", year).unwrap();
+				}
+				let citation = rich::emphasize(rich::from_html(text), r.name);
+				writeln!(out, "{}", inlines(&citation)).unwrap();
+			}
+			out.push('\n');
+		}
+		// Markdown renderers collect footnotes at the end of the document by themselves
+		Section::References => (),
+	}
+}
+
+/// Render the resume as a markdown document.
+pub(crate) fn render(r: &ResumeParams<'_>) -> Result<String, Error> {
+	let mut out = String::new();
+	writeln!(out, "# {}\n", escape(r.name))?;
+	let contacts: Vec<_> = r
+		.contacts
+		.iter()
+		.map(|c| match &c.link {
+			Some(link) => format!("[{}]({})", escape(&c.value), link),
+			None => escape(&c.value),
+		})
+		.collect();
+	if !contacts.is_empty() {
+		writeln!(out, "{}\n", contacts.join(" · "))?;
+	}
+	for s in &r.sections {
+		section(&mut out, r, *s);
+	}
+	// Only define the footnotes that are used
+	let definitions: Vec<_> = r
+		.references
+		.iter()
+		.filter(|(name, _)| out.contains(&format!("[^{}]", name)))
+		.map(|(name, text)| format!("[^{}]: {}", name, inlines(&rich::from_html(text))))
+		.collect();
+	if !definitions.is_empty() {
+		writeln!(out, "{}", definitions.join("\n"))?;
+	}
+	Ok(out.trim_end().to_owned() + "\n")
+}
@@ -282,7 +282,7 @@ where
 				} else {
 					self.write("\n<div class=\"footnote-definition\" id=\"")?;
 				}
-				escape_html(&mut self.writer, &*name)?;
+				escape_html(&mut self.writer, &name)?;
 				self.write("\"><sup class=\"footnote-definition-label\">")?;
 				let len = self.numbers.len() + 1;
 				let number = *self.numbers.entry(name.clone().into_string()).or_insert(len);
@@ -0,0 +1,337 @@
+//! A simplified document model for markdown, used by the output formats that are not HTML.
+use ::html5gum::{Token, Tokenizer};
+use ::pulldown_cmark::{Event, Options, Parser, Tag};
+use ::std::collections::HashMap;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) struct Style {
+	pub(crate) bold: bool,
+	pub(crate) italic: bool,
+	pub(crate) code: bool,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) enum Inline {
+	Text {
+		text: String,
+		style: Style,
+		link: Option<String>,
+	},
+	FootnoteReference(String),
+	LineBreak,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) enum Block {
+	Paragraph(Vec<Inline>),
+	Heading(Vec<Inline>),
+	/// A list of items, numbered from `start` if it is an ordered list
+	List {
+		start: Option<u64>,
+		items: Vec<Vec<Block>>,
+	},
+	Quote(Vec<Block>),
+	Code(String),
+	Rule,
+}
+
+enum Container {
+	Root(Vec<Block>),
+	List {
+		start: Option<u64>,
+		items: Vec<Vec<Block>>,
+	},
+	Item(Vec<Block>),
+	Quote(Vec<Block>),
+}
+
+impl Container {
+	fn push(&mut self, block: Block) {
+		match self {
+			Self::Root(blocks) | Self::Item(blocks) | Self::Quote(blocks) => {
+				blocks.push(block)
+			}
+			// Blocks cannot be direct children of a list
+			Self::List { items, .. } => items.push(vec![block]),
+		}
+	}
+}
+
+#[derive(Default)]
+struct Builder {
+	stack: Vec<Container>,
+	inlines: Vec<Inline>,
+	bold: usize,
+	italic: usize,
+	link: Option<String>,
+	code: Option<String>,
+	in_footnote: bool,
+}
+
+impl Builder {
+	fn style(&self) -> Style {
+		Style {
+			bold: self.bold > 0,
+			italic: self.italic > 0,
+			code: false,
+		}
+	}
+	fn text(&mut self, text: &str, style: Style) {
+		if let Some(Inline::Text {
+			text: last,
+			style: last_style,
+			link,
+		}) = self.inlines.last_mut()
+		{
+			if *last_style == style && *link == self.link {
+				last.push_str(text);
+				return;
+			}
+		}
+		self.inlines.push(Inline::Text {
+			text: text.to_owned(),
+			style,
+			link: self.link.clone(),
+		});
+	}
+	/// Turn the pending inlines into a paragraph, if there are any.
+	fn flush(&mut self) {
+		if !self.inlines.is_empty() {
+			let inlines = ::std::mem::take(&mut self.inlines);
+			self.stack
+				.last_mut()
+				.unwrap()
+				.push(Block::Paragraph(inlines));
+		}
+	}
+	fn event(&mut self, event: Event<'_>) {
+		if self.in_footnote {
+			if let Event::End(Tag::FootnoteDefinition(_)) = event {
+				self.in_footnote = false;
+			}
+			return;
+		}
+		match event {
+			Event::Start(tag) => match tag {
+				Tag::Paragraph | Tag::Heading(..) | Tag::TableRow | Tag::TableHead => {
+					self.flush()
+				}
+				Tag::List(start) => {
+					self.flush();
+					self.stack.push(Container::List {
+						start,
+						items: Vec::new(),
+					});
+				}
+				Tag::Item => self.stack.push(Container::Item(Vec::new())),
+				Tag::BlockQuote => {
+					self.flush();
+					self.stack.push(Container::Quote(Vec::new()));
+				}
+				Tag::CodeBlock(_) => {
+					self.flush();
+					self.code = Some(String::new());
+				}
+				Tag::FootnoteDefinition(_) => self.in_footnote = true,
+				Tag::Emphasis => self.italic += 1,
+				Tag::Strong => self.bold += 1,
+				Tag::Link(_, url, _) => self.link = Some(url.into_string()),
+				Tag::TableCell if !self.inlines.is_empty() => {
+					self.text(" | ", Style::default())
+				}
+				_ => (),
+			},
+			Event::End(tag) => match tag {
+				Tag::Paragraph | Tag::TableRow | Tag::TableHead => self.flush(),
+				Tag::Heading(..) => {
+					let inlines = ::std::mem::take(&mut self.inlines);
+					self.stack.last_mut().unwrap().push(Block::Heading(inlines));
+				}
+				Tag::List(_) | Tag::Item | Tag::BlockQuote => {
+					self.flush();
+					let block = match self.stack.pop().unwrap() {
+						Container::List { start, items } => Block::List { start, items },
+						Container::Item(blocks) => {
+							if let Some(Container::List { items, .. }) = self.stack.last_mut()
+							{
+								items.push(blocks);
+							}
+							return;
+						}
+						Container::Quote(blocks) => Block::Quote(blocks),
+						Container::Root(_) => unreachable!(),
+					};
+					self.stack.last_mut().unwrap().push(block);
+				}
+				Tag::CodeBlock(_) => {
+					let code = self.code.take().unwrap_or_default();
+					self.stack.last_mut().unwrap().push(Block::Code(code));
+				}
+				Tag::Emphasis => self.italic -= 1,
+				Tag::Strong => self.bold -= 1,
+				Tag::Link(..) => self.link = None,
+				_ => (),
+			},
+			Event::Text(text) => {
+				if let Some(code) = self.code.as_mut() {
+					code.push_str(&text);
+				} else {
+					self.text(&text, self.style());
+				}
+			}
+			Event::Code(text) => {
+				let style = Style {
+					code: true,
+					..self.style()
+				};
+				self.text(&text, style)
+			}
+			Event::Html(html) => {
+				for inline in from_html(&html) {
+					match inline {
+						Inline::Text { text, style, .. } => self.text(&text, style),
+						other => self.inlines.push(other),
+					}
+				}
+			}
+			Event::SoftBreak => self.text(" ", self.style()),
+			Event::HardBreak => self.inlines.push(Inline::LineBreak),
+			Event::FootnoteReference(name) => self
+				.inlines
+				.push(Inline::FootnoteReference(name.into_string())),
+			Event::Rule => {
+				self.flush();
+				self.stack.last_mut().unwrap().push(Block::Rule);
+			}
+			Event::TaskListMarker(done) => {
+				self.text(if done { "[x] " } else { "[ ] " }, self.style())
+			}
+		}
+	}
+}
+
+/// Parse markdown into blocks.
+pub(crate) fn parse(s: &str) -> Vec<Block> {
+	let mut builder = Builder {
+		stack: vec![Container::Root(Vec::new())],
+		..Default::default()
+	};
+	for event in Parser::new_ext(s, Options::ENABLE_FOOTNOTES) {
+		builder.event(event);
+	}
+	builder.flush();
+	match builder.stack.pop() {
+		Some(Container::Root(blocks)) => blocks,
+		_ => unreachable!(),
+	}
+}
+
+/// Parse markdown that is rendered inline, joining all of its paragraphs.
+pub(crate) fn parse_inline(s: &str) -> Vec<Inline> {
+	let mut ret = Vec::new();
+	for block in parse(s) {
+		if let Block::Paragraph(inlines) | Block::Heading(inlines) = block {
+			if !ret.is_empty() {
+				ret.push(Inline::LineBreak);
+			}
+			ret.extend(inlines);
+		}
+	}
+	ret
+}
+
+/// Parse the small subset of HTML used in citations: bold, italic, links and line breaks.
+pub(crate) fn from_html(html: &str) -> Vec<Inline> {
+	let mut ret = Vec::new();
+	let mut bold = 0usize;
+	let mut italic = 0usize;
+	let mut link = None;
+	for token in Tokenizer::new(html).infallible() {
+		match token {
+			Token::StartTag(tag) => match tag.name.as_slice() {
+				b"b" | b"strong" => bold += 1,
+				b"i" | b"em" => italic += 1,
+				b"br" => ret.push(Inline::LineBreak),
+				b"a" => {
+					link = tag
+						.attributes
+						.get(b"href".as_slice())
+						.map(|v| String::from_utf8_lossy(v).into_owned())
+				}
+				_ => (),
+			},
+			Token::EndTag(tag) => match tag.name.as_slice() {
+				b"b" | b"strong" => bold = bold.saturating_sub(1),
+				b"i" | b"em" => italic = italic.saturating_sub(1),
+				b"a" => link = None,
+				_ => (),
+			},
+			Token::String(s) => ret.push(Inline::Text {
+				text: String::from_utf8_lossy(&s).into_owned(),
+				style: Style {
+					bold: bold > 0,
+					italic: italic > 0,
+					code: false,
+				},
+				link: link.clone(),
+			}),
+			_ => (),
+		}
+	}
+	ret
+}
+
+/// Make every occurrence of `pat` bold, e.g. to highlight the name of the author.
+pub(crate) fn emphasize(inlines: Vec<Inline>, pat: &str) -> Vec<Inline> {
+	if pat.is_empty() {
+		return inlines;
+	}
+	let mut ret = Vec::new();
+	for inline in inlines {
+		match inline {
+			Inline::Text { text, style, link } if text.contains(pat) => {
+				for (i, part) in text.split(pat).enumerate() {
+					if i > 0 {
+						ret.push(Inline::Text {
+							text: pat.to_owned(),
+							style: Style {
+								bold: true,
+								..style
+							},
+							link: link.clone(),
+						});
+					}
+					if !part.is_empty() {
+						ret.push(Inline::Text {
+							text: part.to_owned(),
+							style,
+							link: link.clone(),
+						});
+					}
+				}
+			}
+			other => ret.push(other),
+		}
+	}
+	ret
+}
+
+/// Numbers footnotes in the order they are referenced.
+#[derive(Default)]
+pub(crate) struct Footnotes(HashMap<String, usize>);
+
+impl Footnotes {
+	pub(crate) fn number(&mut self, name: &str) -> usize {
+		let len = self.0.len() + 1;
+		*self.0.entry(name.to_owned()).or_insert(len)
+	}
+	/// The references that have been used, with their numbers, in order.
+	pub(crate) fn used<'a>(&self, references: &[(&'a str, &'a str)]) -> Vec<(usize, &'a str)> {
+		let mut ret: Vec<_> = references
+			.iter()
+			.filter_map(|(k, v)| self.0.get(*k).map(|n| (*n, *v)))
+			.collect();
+		ret.sort_unstable_by_key(|(n, _)| *n);
+		ret
+	}
+}
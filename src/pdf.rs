@@ -0,0 +1,768 @@
+//! PDF output, laid out directly with the standard PDF fonts, no browser needed.
+//!
+//! Every entry of the resume is laid out as a group of lines which is kept on one page if
+//! possible, mirroring the `pagebreak`/`nopagebreak` rows of the HTML template. Section
+//! headings are kept together with their first entry.
+use crate::md::rich::{self, Block, Footnotes, Inline, Style};
+use crate::{render, Project, ProjectRole, ResumeParams, Section};
+use ::pdf_writer::types::{ActionType, AnnotationType};
+use ::pdf_writer::{Content, Finish, Name, Pdf, Rect, Ref, Str, TextStr};
+use anyhow::Error;
+
+const PAGE_WIDTH: f32 = 595.0;
+const PAGE_HEIGHT: f32 = 842.0;
+const MARGIN: f32 = 50.0;
+const TEXT_WIDTH: f32 = PAGE_WIDTH - 2. * MARGIN;
+const BODY_SIZE: f32 = 10.0;
+const SMALL_SIZE: f32 = 8.5;
+const LEADING: f32 = 1.3;
+const INDENT: f32 = 12.0;
+const GRAY: f32 = 0.4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Font {
+	Regular,
+	Bold,
+	Italic,
+	BoldItalic,
+	Mono,
+}
+
+/// Advance widths of the printable ASCII characters in Helvetica, in 1/1000 em
+#[rustfmt::skip]
+const HELVETICA_WIDTHS: [u16; 95] = [
+	278, 278, 355, 556, 556, 889, 667, 191, 333, 333, 389, 584, 278, 333, 278, 278,
+	556, 556, 556, 556, 556, 556, 556, 556, 556, 556, 278, 278, 584, 584, 584, 556,
+	1015, 667, 667, 722, 722, 667, 611, 778, 722, 278, 500, 667, 556, 833, 722, 778,
+	667, 778, 722, 667, 611, 722, 667, 944, 667, 667, 611, 278, 278, 278, 469, 556,
+	333, 556, 556, 500, 556, 556, 278, 556, 556, 222, 222, 500, 222, 833, 556, 556,
+	556, 556, 333, 500, 278, 556, 500, 722, 500, 500, 500, 334, 260, 334, 584,
+];
+
+/// Advance widths of the printable ASCII characters in Helvetica-Bold, in 1/1000 em
+#[rustfmt::skip]
+const HELVETICA_BOLD_WIDTHS: [u16; 95] = [
+	278, 333, 474, 556, 556, 889, 722, 238, 333, 333, 389, 584, 278, 333, 278, 278,
+	556, 556, 556, 556, 556, 556, 556, 556, 556, 556, 333, 333, 584, 584, 584, 611,
+	975, 722, 722, 722, 722, 667, 611, 778, 722, 278, 556, 722, 611, 833, 722, 778,
+	667, 778, 722, 667, 611, 722, 667, 944, 667, 667, 611, 333, 278, 333, 584, 556,
+	333, 556, 611, 556, 611, 556, 333, 611, 611, 278, 278, 556, 278, 889, 611, 611,
+	611, 611, 389, 556, 333, 611, 556, 778, 556, 556, 500, 389, 280, 389, 584,
+];
+
+impl Font {
+	const ALL: [Font; 5] = [
+		Font::Regular,
+		Font::Bold,
+		Font::Italic,
+		Font::BoldItalic,
+		Font::Mono,
+	];
+	fn from_style(style: Style) -> Font {
+		match (style.code, style.bold, style.italic) {
+			(true, ..) => Font::Mono,
+			(false, true, true) => Font::BoldItalic,
+			(false, true, false) => Font::Bold,
+			(false, false, true) => Font::Italic,
+			(false, false, false) => Font::Regular,
+		}
+	}
+	fn resource_name(self) -> Name<'static> {
+		match self {
+			Font::Regular => Name(b"F1"),
+			Font::Bold => Name(b"F2"),
+			Font::Italic => Name(b"F3"),
+			Font::BoldItalic => Name(b"F4"),
+			Font::Mono => Name(b"F5"),
+		}
+	}
+	fn base_font(self) -> Name<'static> {
+		match self {
+			Font::Regular => Name(b"Helvetica"),
+			Font::Bold => Name(b"Helvetica-Bold"),
+			Font::Italic => Name(b"Helvetica-Oblique"),
+			Font::BoldItalic => Name(b"Helvetica-BoldOblique"),
+			Font::Mono => Name(b"Courier"),
+		}
+	}
+	fn char_width(self, c: char) -> u16 {
+		let widths = match self {
+			Font::Mono => return 600,
+			Font::Regular | Font::Italic => &HELVETICA_WIDTHS,
+			Font::Bold | Font::BoldItalic => &HELVETICA_BOLD_WIDTHS,
+		};
+		match c {
+			' '..='~' => widths[c as usize - 32],
+			'\u{2014}' | '\u{2026}' => 1000,
+			'\u{2022}' => 350,
+			_ => widths[(b'o' - 32) as usize],
+		}
+	}
+	fn width(self, size: f32, text: &str) -> f32 {
+		text.chars().map(|c| self.char_width(c) as f32).sum::<f32>() * size / 1000.
+	}
+}
+
+/// Encode text in WinAnsiEncoding, which is what the standard fonts use. Other characters
+/// can't be written without embedding a font.
+fn encode(text: &str) -> Result<Vec<u8>, Error> {
+	text.chars()
+		.map(|c| match c {
+			'\u{20ac}' => Ok(0x80),
+			'\u{2026}' => Ok(0x85),
+			'\u{2018}' => Ok(0x91),
+			'\u{2019}' => Ok(0x92),
+			'\u{201c}' => Ok(0x93),
+			'\u{201d}' => Ok(0x94),
+			'\u{2022}' => Ok(0x95),
+			'\u{2013}' => Ok(0x96),
+			'\u{2014}' => Ok(0x97),
+			'\u{a0}' => Ok(b' '),
+			' '..='~' | '\u{a1}'..='\u{ff}' => Ok(c as u8),
+			_ => Err(anyhow::anyhow!(
+				"The PDF output can't write {:?} in {:?}, only Western European text",
+				c,
+				text
+			)),
+		})
+		.collect()
+}
+
+#[derive(Debug, Clone)]
+struct Fragment {
+	x: f32,
+	font: Font,
+	size: f32,
+	rise: f32,
+	gray: f32,
+	text: String,
+	link: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+enum Item {
+	Line { size: f32, fragments: Vec<Fragment> },
+	Rule,
+	Space(f32),
+}
+
+impl Item {
+	fn height(&self) -> f32 {
+		match self {
+			Item::Line { size, .. } => size * LEADING,
+			Item::Rule => 4.,
+			Item::Space(h) => *h,
+		}
+	}
+}
+
+/// Breaks runs of text into lines.
+struct Wrapper {
+	lines: Vec<Item>,
+	fragments: Vec<Fragment>,
+	left: f32,
+	right: f32,
+	x: f32,
+	size: f32,
+	line_size: f32,
+	pending_space: bool,
+}
+
+impl Wrapper {
+	fn new(left: f32, right: f32, size: f32) -> Self {
+		Self {
+			lines: Vec::new(),
+			fragments: Vec::new(),
+			left,
+			right,
+			x: left,
+			size,
+			line_size: size,
+			pending_space: false,
+		}
+	}
+	fn break_line(&mut self) {
+		self.lines.push(Item::Line {
+			size: self.line_size,
+			fragments: ::std::mem::take(&mut self.fragments),
+		});
+		self.x = self.left;
+		self.line_size = self.size;
+		self.pending_space = false;
+	}
+	fn place(
+		&mut self,
+		word: &str,
+		font: Font,
+		size: f32,
+		rise: f32,
+		gray: f32,
+		link: &Option<String>,
+	) {
+		let space = if self.pending_space && !self.fragments.is_empty() {
+			font.width(size, " ")
+		} else {
+			0.
+		};
+		let width = font.width(size, word);
+		if !self.fragments.is_empty() && self.x + space + width > self.right {
+			self.break_line();
+			return self.place(word, font, size, rise, gray, link);
+		}
+		self.pending_space = false;
+		if let Some(last) = self.fragments.last_mut() {
+			if last.font == font
+				&& last.size == size
+				&& last.rise == rise
+				&& last.gray == gray
+				&& last.link == *link
+			{
+				if space > 0. {
+					last.text.push(' ');
+				}
+				last.text.push_str(word);
+				self.x += space + width;
+				return;
+			}
+		}
+		self.fragments.push(Fragment {
+			x: self.x + space,
+			font,
+			size,
+			rise,
+			gray,
+			text: word.to_owned(),
+			link: link.clone(),
+		});
+		self.x += space + width;
+	}
+	fn push(&mut self, text: &str, font: Font, size: f32, gray: f32, link: &Option<String>) {
+		self.line_size = self.line_size.max(size);
+		let mut first = true;
+		for word in text.split(char::is_whitespace) {
+			if !first {
+				self.pending_space = true;
+			}
+			first = false;
+			if !word.is_empty() {
+				self.place(word, font, size, 0., gray, link);
+			}
+		}
+	}
+	fn push_inlines(&mut self, inlines: &[Inline], footnotes: &mut Footnotes) {
+		for inline in inlines {
+			match inline {
+				Inline::Text { text, style, link } => {
+					let gray = if link.is_some() { 0.1 } else { 0. };
+					self.push(text, Font::from_style(*style), self.size, gray, link)
+				}
+				Inline::FootnoteReference(name) => {
+					let number = footnotes.number(name).to_string();
+					let link = None;
+					self.place(
+						&number,
+						Font::Regular,
+						self.size * 0.7,
+						self.size * 0.35,
+						0.,
+						&link,
+					);
+				}
+				Inline::LineBreak => self.break_line(),
+			}
+		}
+	}
+	fn finish(mut self) -> Vec<Item> {
+		if !self.fragments.is_empty() {
+			self.break_line();
+		}
+		self.lines
+	}
+}
+
+/// Lines which should be kept on the same page.
+#[derive(Default)]
+struct Group {
+	items: Vec<Item>,
+	/// Whether the next group should be kept on the same page as this one
+	keep_with_next: bool,
+}
+
+impl Group {
+	fn text(&mut self, left: f32, size: f32, font: Font, gray: f32, text: &str) {
+		let mut w = Wrapper::new(left, MARGIN + TEXT_WIDTH, size);
+		w.push(text, font, size, gray, &None);
+		self.items.extend(w.finish());
+	}
+	fn inlines(
+		&mut self,
+		left: f32,
+		size: f32,
+		inlines: &[Inline],
+		footnotes: &mut Footnotes,
+	) {
+		let mut w = Wrapper::new(left, MARGIN + TEXT_WIDTH, size);
+		w.push_inlines(inlines, footnotes);
+		self.items.extend(w.finish());
+	}
+	/// A line with `left` on the left, and `right` aligned to the right margin.
+	fn title(&mut self, left: Vec<(Font, f32, &str)>, right: &str) {
+		let mut w = Wrapper::new(MARGIN, MARGIN + TEXT_WIDTH, BODY_SIZE);
+		for (font, gray, text) in left {
+			w.line_size = w.line_size.max(BODY_SIZE + 1.);
+			w.push(text, font, BODY_SIZE + 1., gray, &None);
+		}
+		let mut lines = w.finish();
+		if !right.is_empty() {
+			let width = Font::Regular.width(SMALL_SIZE, right);
+			if let Some(Item::Line { fragments, .. }) = lines.first_mut() {
+				fragments.push(Fragment {
+					x: MARGIN + TEXT_WIDTH - width,
+					font: Font::Regular,
+					size: SMALL_SIZE,
+					rise: 0.,
+					gray: GRAY,
+					text: right.to_owned(),
+					link: None,
+				});
+			}
+		}
+		self.items.extend(lines);
+	}
+	fn blocks(&mut self, left: f32, blocks: &[Block], footnotes: &mut Footnotes) {
+		for block in blocks {
+			match block {
+				Block::Paragraph(inlines) => {
+					self.inlines(left, BODY_SIZE, inlines, footnotes);
+					self.items.push(Item::Space(3.));
+				}
+				Block::Heading(inlines) => {
+					let bold: Vec<_> = inlines
+						.iter()
+						.cloned()
+						.map(|i| match i {
+							Inline::Text { text, style, link } => Inline::Text {
+								text,
+								style: Style {
+									bold: true,
+									..style
+								},
+								link,
+							},
+							other => other,
+						})
+						.collect();
+					self.inlines(left, BODY_SIZE, &bold, footnotes);
+				}
+				Block::List { start, items } => {
+					for (i, item) in items.iter().enumerate() {
+						let marker = match start {
+							Some(start) => format!("{}.", start + i as u64),
+							None => "\u{2022}".to_owned(),
+						};
+						let first = self.items.len();
+						self.blocks(left + INDENT, item, footnotes);
+						// Put the marker on the first line of the item
+						if let Some(Item::Line { fragments, .. }) = self.items[first..]
+							.iter_mut()
+							.find(|i| matches!(i, Item::Line { .. }))
+						{
+							fragments.push(Fragment {
+								x: left + 2.,
+								font: Font::Regular,
+								size: BODY_SIZE,
+								rise: 0.,
+								gray: 0.,
+								text: marker,
+								link: None,
+							});
+						}
+						// Tighten the spacing between items
+						if let Some(Item::Space(_)) = self.items.last() {
+							self.items.pop();
+						}
+					}
+					self.items.push(Item::Space(3.));
+				}
+				Block::Quote(blocks) => self.blocks(left + INDENT, blocks, footnotes),
+				Block::Code(code) => {
+					for line in code.lines() {
+						self.items.push(Item::Line {
+							size: SMALL_SIZE,
+							fragments: vec![Fragment {
+								x: left,
+								font: Font::Mono,
+								size: SMALL_SIZE,
+								rise: 0.,
+								gray: 0.,
+								text: line.to_owned(),
+								link: None,
+							}],
+						});
+					}
+					self.items.push(Item::Space(3.));
+				}
+				Block::Rule => self.items.push(Item::Rule),
+			}
+		}
+	}
+}
+
+fn heading(text: &str, size: f32, rule: bool) -> Group {
+	let mut g = Group {
+		keep_with_next: true,
+		..Default::default()
+	};
+	g.items.push(Item::Space(size * 0.6));
+	g.text(MARGIN, size, Font::Bold, 0., text);
+	if rule {
+		g.items.push(Item::Rule);
+	}
+	g
+}
+
+fn entry_end(g: &mut Group) {
+	if let Some(Item::Space(_)) = g.items.last() {
+		g.items.pop();
+	}
+	g.items.push(Item::Space(6.));
+}
+
+fn project(p: &Project, footnotes: &mut Footnotes) -> Group {
+	let mut g = Group::default();
+	let owner = render::project_owner(p)
+		.map(|owner| format!("{}/", owner))
+		.unwrap_or_default();
+	g.title(
+		vec![(Font::Regular, GRAY, &owner), (Font::Bold, 0., &p.name)],
+		&render::project_stats(p).join(" \u{b7} "),
+	);
+	if p.role == Some(ProjectRole::Contributor) {
+		if let Some(contributions) = &p.contributions {
+			g.inlines(
+				MARGIN,
+				BODY_SIZE,
+				&rich::parse_inline(contributions),
+				footnotes,
+			);
+		}
+		entry_end(&mut g);
+		return g;
+	}
+	if !p.languages.is_empty() {
+		let languages: Vec<_> = p
+			.languages
+			.iter()
+			.take(4)
+			.map(|l| format!("{} {}%", l.language, l.percentage))
+			.collect();
+		g.text(
+			MARGIN,
+			SMALL_SIZE,
+			Font::Regular,
+			GRAY,
+			&languages.join(" \u{b7} "),
+		);
+	}
+	if let Some(description) = &p.description {
+		g.blocks(MARGIN, &rich::parse(description), footnotes);
+	}
+	if !p.tags.is_empty() {
+		let tags: Vec<_> = p.tags.iter().map(|t| format!("#{}", t)).collect();
+		g.text(MARGIN, SMALL_SIZE, Font::Regular, GRAY, &tags.join(" "));
+	}
+	if let Some(contributions) = &p.contributions {
+		g.text(MARGIN, BODY_SIZE, Font::Italic, 0., "Contributions");
+		g.blocks(MARGIN, &rich::parse(contributions), footnotes);
+	}
+	entry_end(&mut g);
+	g
+}
+
+fn section(r: &ResumeParams<'_>, s: Section, footnotes: &mut Footnotes) -> Vec<Group> {
+	let mut groups = Vec::new();
+	match s {
+		Section::Education => {
+			groups.push(heading("Education", 13., true));
+			for e in r.educations {
+				let mut g = Group::default();
+				g.title(
+					vec![(Font::Bold, 0., &e.institution)],
+					&e.duration.to_text_string(),
+				);
+				let mut subtitle = format!("{}, {}", e.degree.to_resume_string(), e.major);
+				if let Some(location) = &e.location {
+					subtitle.push_str(&format!(" \u{b7} {}", location));
+				}
+				if let Some(gpa) = e.gpa {
+					subtitle.push_str(&format!(" \u{b7} GPA {}", gpa));
+				}
+				g.text(MARGIN, BODY_SIZE, Font::Italic, 0., &subtitle);
+				entry_end(&mut g);
+				groups.push(g);
+			}
+		}
+		Section::Work => {
+			groups.push(heading("Work", 13., true));
+			for e in r.experiences {
+				let mut g = Group::default();
+				g.title(
+					vec![(Font::Bold, 0., &e.company)],
+					&e.duration.to_text_string(),
+				);
+				let mut subtitle = e.position.clone();
+				if let Some(location) = &e.location {
+					subtitle.push_str(&format!(" \u{b7} {}", location));
+				}
+				g.text(MARGIN, BODY_SIZE, Font::Italic, 0., &subtitle);
+				g.items.push(Item::Space(2.));
+				g.blocks(MARGIN, &rich::parse(&e.description), footnotes);
+				entry_end(&mut g);
+				groups.push(g);
+			}
+		}
+		Section::OpenSource => {
+			groups.push(heading("Open Source", 13., true));
+			for (role, title) in render::PROJECT_SECTIONS {
+				if r.has_projects(role) {
+					groups.push(heading(title, 11., false));
+					for p in r.projects.iter().filter(|p| p.role == Some(role)) {
+						groups.push(project(p, footnotes));
+					}
+				}
+			}
+		}
+		Section::Skills => {
+			groups.push(heading("Skills", 13., true));
+			for s in r.skills {
+				let mut inlines = vec![Inline::Text {
+					text: format!("{}: ", s.category),
+					style: Style {
+						bold: true,
+						..Default::default()
+					},
+					link: None,
+				}];
+				if let Some(description) = &s.description {
+					inlines.extend(rich::parse_inline(description));
+				}
+				let mut g = Group::default();
+				g.inlines(MARGIN, BODY_SIZE, &inlines, footnotes);
+				g.items.push(Item::Space(3.));
+				groups.push(g);
+			}
+		}
+		Section::Publications => {
+			groups.push(heading("Publications", 13., true));
+			for (text, year) in &r.publications {
+				let mut g = Group::default();
+				let first = g.items.len();
+				g.inlines(
+					MARGIN + 36.,
+					BODY_SIZE,
+					&rich::emphasize(rich::from_html(text), r.name),
+					footnotes,
+				);
+				if let (Some(year), Some(Item::Line { fragments, .. })) =
+					(year, g.items.get_mut(first))
+				{
+					fragments.push(Fragment {
+						x: MARGIN,
+						font: Font::Bold,
+						size: BODY_SIZE,
+						rise: 0.,
+						gray: 0.,
+						text: year.to_string(),
+						link: None,
+					});
+				}
+				g.items.push(Item::Space(4.));
+				groups.push(g);
+			}
+		}
+		Section::References => {
+			let references = footnotes.used(&r.references);
+			if references.is_empty() {
+				return groups;
+			}
+			groups.push(heading("References", 13., true));
+			for (n, text) in references {
+				let mut g = Group::default();
+				let mut inlines = vec![Inline::Text {
+					text: format!("[{}] ", n),
+					style: Style::default(),
+					link: None,
+				}];
+				inlines.extend(rich::from_html(text));
+				g.inlines(MARGIN, SMALL_SIZE, &inlines, footnotes);
+				g.items.push(Item::Space(2.));
+				groups.push(g);
+			}
+		}
+	}
+	groups
+}
+
+/// Distribute the groups onto pages, returning each item with its top position.
+fn paginate(groups: Vec<Group>) -> Vec<Vec<(f32, Item)>> {
+	let top = PAGE_HEIGHT - MARGIN;
+	let usable = PAGE_HEIGHT - 2. * MARGIN;
+	let mut pages = vec![Vec::new()];
+	let mut y = top;
+	let mut pending: Vec<Group> = Vec::new();
+	for group in groups {
+		let keep_with_next = group.keep_with_next;
+		pending.push(group);
+		if keep_with_next {
+			continue;
+		}
+		let items: Vec<_> = pending.drain(..).flat_map(|g| g.items).collect();
+		let height: f32 = items.iter().map(Item::height).sum();
+		if y - height < MARGIN && height <= usable && y < top {
+			pages.push(Vec::new());
+			y = top;
+		}
+		for item in items {
+			if matches!(item, Item::Space(_)) && y == top {
+				continue;
+			}
+			if y - item.height() < MARGIN {
+				pages.push(Vec::new());
+				y = top;
+			}
+			let h = item.height();
+			pages.last_mut().unwrap().push((y, item));
+			y -= h;
+		}
+	}
+	pages
+}
+
+/// Render the resume as a PDF document.
+pub(crate) fn render(r: &ResumeParams<'_>) -> Result<Vec<u8>, Error> {
+	let mut footnotes = Footnotes::default();
+	let mut header = Group::default();
+	header.text(MARGIN, 22., Font::Bold, 0., r.name);
+	header.items.push(Item::Space(2.));
+	let mut contacts = Wrapper::new(MARGIN, MARGIN + TEXT_WIDTH, SMALL_SIZE);
+	for (i, c) in r.contacts.iter().enumerate() {
+		if i > 0 {
+			contacts.push(" \u{b7} ", Font::Regular, SMALL_SIZE, GRAY, &None);
+		}
+		contacts.push(&c.value, Font::Regular, SMALL_SIZE, 0., &c.link);
+	}
+	header.items.extend(contacts.finish());
+	header.items.push(Item::Space(6.));
+
+	let sections = render::sections(&r.sections, |s| section(r, s, &mut footnotes));
+	let pages = paginate(
+		::std::iter::once(header)
+			.chain(sections.into_iter().flatten())
+			.collect(),
+	);
+
+	let mut pdf = Pdf::new();
+	let catalog_id = Ref::new(1);
+	let page_tree_id = Ref::new(2);
+	let info_id = Ref::new(3);
+	let mut next_id = 4;
+	let mut alloc = || {
+		next_id += 1;
+		Ref::new(next_id - 1)
+	};
+	let font_ids: Vec<_> = Font::ALL.iter().map(|_| alloc()).collect();
+	let page_ids: Vec<_> = pages.iter().map(|_| alloc()).collect();
+	let content_ids: Vec<_> = pages.iter().map(|_| alloc()).collect();
+
+	pdf.catalog(catalog_id).pages(page_tree_id);
+	pdf.pages(page_tree_id)
+		.kids(page_ids.iter().copied())
+		.count(pages.len() as i32);
+	pdf.document_info(info_id)
+		.title(TextStr(r.name))
+		.author(TextStr(r.name))
+		.creator(TextStr("briefly"));
+	for (font, id) in Font::ALL.iter().zip(&font_ids) {
+		pdf.type1_font(*id)
+			.base_font(font.base_font())
+			.encoding_predefined(Name(b"WinAnsiEncoding"));
+	}
+
+	let page_count = pages.len();
+	for (n, items) in pages.into_iter().enumerate() {
+		let mut content = Content::new();
+		let mut links = Vec::new();
+		for (y, item) in items {
+			match item {
+				Item::Line { size, fragments } => {
+					let baseline = y - size;
+					for f in fragments {
+						content
+							.begin_text()
+							.set_font(f.font.resource_name(), f.size)
+							.set_fill_gray(f.gray)
+							.set_rise(f.rise)
+							.next_line(f.x, baseline)
+							.show(Str(&encode(&f.text)?))
+							.end_text();
+						if let Some(link) = f.link {
+							let width = f.font.width(f.size, &f.text);
+							links.push((
+								Rect::new(f.x, baseline - 2., f.x + width, baseline + f.size),
+								link,
+							));
+						}
+					}
+				}
+				Item::Rule => {
+					content
+						.set_stroke_gray(GRAY)
+						.set_line_width(0.5)
+						.move_to(MARGIN, y - 2.)
+						.line_to(MARGIN + TEXT_WIDTH, y - 2.)
+						.stroke();
+				}
+				Item::Space(_) => (),
+			}
+		}
+		if page_count > 1 {
+			let number = format!("{} / {}", n + 1, page_count);
+			let width = Font::Regular.width(SMALL_SIZE, &number);
+			content
+				.begin_text()
+				.set_font(Font::Regular.resource_name(), SMALL_SIZE)
+				.set_fill_gray(GRAY)
+				.next_line((PAGE_WIDTH - width) / 2., MARGIN / 2.)
+				.show(Str(number.as_bytes()))
+				.end_text();
+		}
+		pdf.stream(content_ids[n], &content.finish());
+
+		let mut page = pdf.page(page_ids[n]);
+		page.media_box(Rect::new(0., 0., PAGE_WIDTH, PAGE_HEIGHT))
+			.parent(page_tree_id)
+			.contents(content_ids[n]);
+		let mut resources = page.resources();
+		let mut fonts = resources.fonts();
+		for (font, id) in Font::ALL.iter().zip(&font_ids) {
+			fonts.pair(font.resource_name(), *id);
+		}
+		fonts.finish();
+		resources.finish();
+		let mut annotations = page.annotations();
+		for (rect, link) in &links {
+			let mut annotation = annotations.push();
+			annotation
+				.subtype(AnnotationType::Link)
+				.rect(*rect)
+				.border(0., 0., 0., None);
+			annotation
+				.action()
+				.action_type(ActionType::Uri)
+				.uri(Str(link.as_bytes()));
+		}
+		annotations.finish();
+		page.finish();
+	}
+	Ok(pdf.finish())
+}
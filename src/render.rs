@@ -0,0 +1,54 @@
+//! Parts of the resume layout shared by the output formats that are not HTML.
+use crate::{Project, ProjectRole, Section};
+
+/// The subsections of the open source section, in order, with their titles.
+pub(crate) const PROJECT_SECTIONS: [(ProjectRole, &str); 3] = [
+	(ProjectRole::Owner, "Projects"),
+	(ProjectRole::Maintainer, "Maintained Projects"),
+	(ProjectRole::Contributor, "Contributions"),
+];
+
+/// The owner shown before the name of a project the user doesn't own.
+pub(crate) fn project_owner(p: &Project) -> Option<&str> {
+	match (p.role, &p.owner) {
+		(Some(ProjectRole::Contributor | ProjectRole::Maintainer), Some(owner)) => Some(owner),
+		_ => None,
+	}
+}
+
+/// Stars and forks of a project, or only that it is archived.
+pub(crate) fn project_stats(p: &Project) -> Vec<String> {
+	let mut stats = Vec::new();
+	if p.active == Some(false) {
+		stats.push("archived".to_owned());
+	} else {
+		if let Some(stars) = p.stars {
+			stats.push(format!("{} stars", stars));
+		}
+		if let Some(forks) = p.forks {
+			stats.push(format!("{} forks", forks));
+		}
+	}
+	stats
+}
+
+/// Render `sections` in order with `render`. References are numbered as they are used, so
+/// they are rendered last.
+pub(crate) fn sections<T: Default>(
+	sections: &[Section],
+	mut render: impl FnMut(Section) -> T,
+) -> Vec<T> {
+	let mut ret: Vec<_> = sections
+		.iter()
+		.map(|s| match s {
+			Section::References => T::default(),
+			_ => render(*s),
+		})
+		.collect();
+	for (i, s) in sections.iter().enumerate() {
+		if *s == Section::References {
+			ret[i] = render(*s);
+		}
+	}
+	ret
+}
@@ -0,0 +1,102 @@
+//! Themes loaded at runtime from a user-supplied template directory.
+//!
+//! The templates are written in the Jinja dialect understood by `minijinja`, and get the same
+//! data and filters as the built-in askama template. The entry point is `resume.html`.
+use crate::{filters, DateRange, Degree, LanguageStat, ResumeParams};
+use ::minijinja::value::Value;
+use ::minijinja::{AutoEscape, Environment, ErrorKind};
+use ::std::path::Path;
+use anyhow::Error;
+
+pub(crate) struct Theme {
+	env: Environment<'static>,
+}
+
+fn error(e: impl ::std::fmt::Display) -> ::minijinja::Error {
+	::minijinja::Error::new(ErrorKind::InvalidOperation, e.to_string())
+}
+
+/// Convert a template value back into one of our own types.
+fn from_value<T: ::serde::de::DeserializeOwned>(v: &Value) -> Result<T, ::minijinja::Error> {
+	serde_json::to_value(v)
+		.and_then(serde_json::from_value)
+		.map_err(error)
+}
+
+fn md(s: String) -> Result<String, ::minijinja::Error> {
+	filters::md(s).map_err(error)
+}
+
+fn inline_md(s: String) -> Result<String, ::minijinja::Error> {
+	filters::inline_md(s).map_err(error)
+}
+
+/// Empty lists are left out of the serialized projects, so there is nothing to show for
+/// missing languages.
+fn language_stats(languages: Value) -> Result<String, ::minijinja::Error> {
+	if languages.is_undefined() || languages.is_none() {
+		return Ok(String::new());
+	}
+	filters::language_stats(&from_value::<Vec<LanguageStat>>(&languages)?).map_err(error)
+}
+
+fn emph(s: String, pat: String) -> Result<String, ::minijinja::Error> {
+	filters::emph(&s, &pat).map_err(error)
+}
+
+fn asset(path: String) -> Result<String, ::minijinja::Error> {
+	filters::asset(&path).map_err(error)
+}
+
+fn stylesheet(path: String, media: String) -> Result<String, ::minijinja::Error> {
+	filters::stylesheet(&path, &media).map_err(error)
+}
+
+fn data_uri(content: String, mime: String) -> Result<String, ::minijinja::Error> {
+	filters::data_uri(&content, &mime).map_err(error)
+}
+
+/// Formats a serialized `DateRange`, like `to_resume_string` does.
+fn date_range(range: String) -> Result<String, ::minijinja::Error> {
+	Ok(range
+		.parse::<DateRange>()
+		.map_err(error)?
+		.to_resume_string())
+}
+
+/// Formats a serialized `Degree`, like `to_resume_string` does.
+fn degree(degree: Value) -> Result<String, ::minijinja::Error> {
+	Ok(from_value::<Degree>(&degree)?.to_resume_string())
+}
+
+impl Theme {
+	pub(crate) fn load(dir: &Path) -> Result<Self, Error> {
+		if !dir.join("resume.html").is_file() {
+			return Err(anyhow::anyhow!(
+				"{} does not contain a resume.html template",
+				dir.display()
+			));
+		}
+		let mut env = Environment::new();
+		env.set_loader(::minijinja::path_loader(dir));
+		env.set_auto_escape_callback(|_| AutoEscape::None);
+		env.add_filter("md", md);
+		env.add_filter("inline_md", inline_md);
+		env.add_filter("language_stats", language_stats);
+		env.add_filter("emph", emph);
+		env.add_filter("asset", asset);
+		env.add_filter("stylesheet", stylesheet);
+		env.add_filter("data_uri", data_uri);
+		env.add_filter("date_range", date_range);
+		env.add_filter("degree", degree);
+		crate::assets::add_search_dir(dir);
+		Ok(Self { env })
+	}
+
+	pub(crate) fn render(&self, params: &ResumeParams<'_>) -> Result<String, Error> {
+		Ok(self
+			.env
+			.get_template("resume.html")?
+			.render(Value::from_serialize(params))?)
+	}
+}
@@ -0,0 +1,246 @@
+//! Plain text output, which applicant tracking systems can parse reliably.
+//!
+//! Markdown is flattened, links are written out after their text, and references are numbered
+//! in the order they are used.
+use crate::md::rich::{self, Block, Footnotes, Inline};
+use crate::{render, Project, ProjectRole, ResumeParams, Section};
+use ::std::fmt::Write;
+use anyhow::Error;
+
+const WIDTH: usize = 80;
+
+fn flatten(inlines: &[Inline], footnotes: &mut Footnotes) -> String {
+	let mut ret = String::new();
+	for inline in inlines {
+		match inline {
+			Inline::Text { text, link, .. } => {
+				ret.push_str(text);
+				if let Some(link) = link {
+					let link = link.strip_prefix("mailto:").unwrap_or(link);
+					if link != text {
+						write!(ret, " ({})", link).unwrap();
+					}
+				}
+			}
+			Inline::FootnoteReference(name) => {
+				write!(ret, "[{}]", footnotes.number(name)).unwrap()
+			}
+			Inline::LineBreak => ret.push('\n'),
+		}
+	}
+	ret
+}
+
+/// Wrap `text` to the page width, with `first` in front of the first line, and every other
+/// line indented as deep as `first`.
+fn wrap(out: &mut String, first: &str, indent: usize, text: &str) {
+	let rest = " ".repeat(indent);
+	let mut first = format!("{}{}", rest, first);
+	let subsequent = " ".repeat(first.chars().count());
+	if text.is_empty() {
+		writeln!(out, "{}", first.trim_end()).unwrap();
+		return;
+	}
+	for line in text.lines() {
+		let options = ::textwrap::Options::new(WIDTH)
+			.initial_indent(&first)
+			.subsequent_indent(&subsequent);
+		for wrapped in ::textwrap::wrap(line, options) {
+			out.push_str(wrapped.trim_end());
+			out.push('\n');
+		}
+		first = subsequent.clone();
+	}
+}
+
+fn blocks(out: &mut String, indent: usize, blocks: &[Block], footnotes: &mut Footnotes) {
+	for block in blocks {
+		match block {
+			Block::Paragraph(inlines) | Block::Heading(inlines) => {
+				wrap(out, "", indent, &flatten(inlines, footnotes));
+			}
+			Block::List { start, items } => {
+				for (i, item) in items.iter().enumerate() {
+					let marker = match start {
+						Some(start) => format!("{}. ", start + i as u64),
+						None => "- ".to_owned(),
+					};
+					// Put the marker in front of the first paragraph
+					let rest = match item.split_first() {
+						Some((Block::Paragraph(inlines), rest)) => {
+							wrap(out, &marker, indent, &flatten(inlines, footnotes));
+							rest
+						}
+						_ => {
+							wrap(out, &marker, indent, "");
+							&item[..]
+						}
+					};
+					self::blocks(out, indent + marker.len(), rest, footnotes);
+				}
+			}
+			Block::Quote(quoted) => self::blocks(out, indent + 4, quoted, footnotes),
+			Block::Code(code) => {
+				for line in code.lines() {
+					writeln!(out, "{}{}", " ".repeat(indent + 4), line).unwrap();
+				}
+			}
+			Block::Rule => (),
+		}
+	}
+}
+
+fn project(out: &mut String, n: usize, p: &Project, footnotes: &mut Footnotes) {
+	let marker = format!("{}. ", n);
+	let indent = marker.len();
+	let mut title = match render::project_owner(p) {
+		Some(owner) => format!("{}/{}", owner, p.name),
+		None => p.name.clone(),
+	};
+	if let Some(url) = &p.url {
+		write!(title, " ({})", url).unwrap();
+	}
+	wrap(out, &marker, 0, &title);
+	if p.role == Some(ProjectRole::Contributor) {
+		if let Some(contributions) = &p.contributions {
+			let text = flatten(&rich::parse_inline(contributions), footnotes);
+			wrap(out, "", indent, &text);
+		}
+		return;
+	}
+	let mut stats = render::project_stats(p);
+	if !p.languages.is_empty() {
+		let languages: Vec<_> = p
+			.languages
+			.iter()
+			.map(|l| format!("{} {}%", l.language, l.percentage))
+			.collect();
+		stats.push(format!("Languages: {}", languages.join(", ")));
+	}
+	if !stats.is_empty() {
+		wrap(out, "", indent, &stats.join(", "));
+	}
+	if let Some(description) = &p.description {
+		blocks(out, indent, &rich::parse(description), footnotes);
+	}
+	if !p.tags.is_empty() {
+		wrap(out, "", indent, &format!("Tags: {}", p.tags.join(", ")));
+	}
+	if let Some(contributions) = &p.contributions {
+		wrap(out, "", indent, "Contributions:");
+		blocks(out, indent, &rich::parse(contributions), footnotes);
+	}
+}
+
+fn section(
+	out: &mut String,
+	r: &ResumeParams<'_>,
+	section: Section,
+	footnotes: &mut Footnotes,
+) {
+	match section {
+		Section::Education => {
+			out.push_str("EDUCATION\n\n");
+			for e in r.educations {
+				let mut subtitle = format!("{}, {}", e.degree.to_resume_string(), e.major);
+				if let Some(gpa) = e.gpa {
+					write!(subtitle, ", GPA {}", gpa).unwrap();
+				}
+				wrap(out, "", 0, &e.institution);
+				wrap(out, "", 0, &subtitle);
+				if let Some(location) = &e.location {
+					wrap(out, "", 0, location);
+				}
+				writeln!(out, "{}\n", e.duration.to_text_string()).unwrap();
+			}
+		}
+		Section::Work => {
+			out.push_str("WORK EXPERIENCE\n\n");
+			for e in r.experiences {
+				wrap(out, "", 0, &format!("{}, {}", e.position, e.company));
+				if let Some(location) = &e.location {
+					wrap(out, "", 0, location);
+				}
+				writeln!(out, "{}", e.duration.to_text_string()).unwrap();
+				blocks(out, 0, &rich::parse(&e.description), footnotes);
+				out.push('\n');
+			}
+		}
+		Section::OpenSource => {
+			out.push_str("OPEN SOURCE\n\n");
+			for (role, title) in render::PROJECT_SECTIONS {
+				if r.has_projects(role) {
+					writeln!(out, "{}:\n", title).unwrap();
+					for (i, p) in r
+						.projects
+						.iter()
+						.filter(|p| p.role == Some(role))
+						.enumerate()
+					{
+						project(out, i + 1, p, footnotes);
+						out.push('\n');
+					}
+				}
+			}
+		}
+		Section::Skills => {
+			out.push_str("SKILLS\n\n");
+			for s in r.skills {
+				let description = s
+					.description
+					.as_deref()
+					.map(|d| flatten(&rich::parse_inline(d), footnotes))
+					.unwrap_or_default();
+				wrap(out, &format!("{}: ", s.category), 0, &description);
+			}
+			out.push('\n');
+		}
+		Section::Publications => {
+			out.push_str("PUBLICATIONS\n\n");
+			for (i, (text, year)) in r.publications.iter().enumerate() {
+				let mut text = flatten(&rich::from_html(text), footnotes);
+				if let Some(year) = year {
+					text = format!("{} ({})", text, year);
+				}
+				wrap(out, &format!("{}. ", i + 1), 0, &text);
+			}
+			out.push('\n');
+		}
+		Section::References => {
+			let references = footnotes.used(&r.references);
+			if references.is_empty() {
+				return;
+			}
+			out.push_str("REFERENCES\n\n");
+			for (n, text) in references {
+				let text = flatten(&rich::from_html(text), footnotes);
+				wrap(out, &format!("[{}] ", n), 0, &text);
+			}
+			out.push('\n');
+		}
+	}
+}
+
+/// Render the resume as plain text.
+pub(crate) fn render(r: &ResumeParams<'_>) -> Result<String, Error> {
+	let mut footnotes = Footnotes::default();
+	let mut out = String::new();
+	writeln!(out, "{}", r.name.to_uppercase())?;
+	for c in &r.contacts {
+		match c.link.as_deref() {
+			Some(link) if link.starts_with("http") && link != c.value => {
+				writeln!(out, "{} ({})", c.value, link)?
+			}
+			_ => writeln!(out, "{}", c.value)?,
+		}
+	}
+	out.push('\n');
+
+	let sections = render::sections(&r.sections, |s| {
+		let mut text = String::new();
+		section(&mut text, r, s, &mut footnotes);
+		text
+	});
+	out.push_str(&sections.concat());
+	Ok(out.trim_end().to_owned() + "\n")
+}
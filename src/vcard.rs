@@ -0,0 +1,101 @@
+//! vCard 4.0 (RFC 6350) export of the contact information.
+use crate::ResumeParams;
+use ::std::fmt::Write;
+
+/// Escape a property value.
+fn escape(s: &str) -> String {
+	let mut ret = String::with_capacity(s.len());
+	for c in s.chars() {
+		match c {
+			'\\' | ',' | ';' => {
+				ret.push('\\');
+				ret.push(c);
+			}
+			'\n' => ret.push_str("\\n"),
+			_ => ret.push(c),
+		}
+	}
+	ret
+}
+
+/// Quote a parameter value, with the caret encoding of RFC 6868 for the characters that can't
+/// appear in it.
+fn param(s: &str) -> String {
+	let mut ret = String::with_capacity(s.len() + 2);
+	ret.push('"');
+	for c in s.chars() {
+		match c {
+			'^' => ret.push_str("^^"),
+			'"' => ret.push_str("^'"),
+			'\n' => ret.push_str("^n"),
+			_ => ret.push(c),
+		}
+	}
+	ret.push('"');
+	ret
+}
+
+/// Write a content line, folded so no line is longer than 75 octets.
+fn line(out: &mut String, content: &str) {
+	let mut width = 0;
+	for c in content.chars() {
+		if width + c.len_utf8() > 75 {
+			out.push_str("\r\n ");
+			width = 1;
+		}
+		out.push(c);
+		width += c.len_utf8();
+	}
+	out.push_str("\r\n");
+}
+
+/// Render the name and contacts of the resume as a vCard.
+pub(crate) fn render(r: &ResumeParams<'_>) -> String {
+	let mut out = String::new();
+	line(&mut out, "BEGIN:VCARD");
+	line(&mut out, "VERSION:4.0");
+	line(&mut out, &format!("FN:{}", escape(r.name)));
+	// Assume the family name comes last, which is only a guess for structured names
+	let (given, family) = r.name.rsplit_once(' ').unwrap_or(("", r.name));
+	line(
+		&mut out,
+		&format!("N:{};{};;;", escape(family), escape(given)),
+	);
+	for c in &r.contacts {
+		let mut content = String::new();
+		match (c.type_.as_str(), c.link.as_deref()) {
+			("email", _) => write!(content, "EMAIL:{}", escape(&c.value)),
+			("phone", _) => write!(
+				content,
+				"TEL;VALUE=uri:tel:{}",
+				c.value.split_whitespace().collect::<Vec<_>>().join("-")
+			),
+			("blog" | "url" | "website", _) => {
+				write!(content, "URL:{}", c.link.as_deref().unwrap_or(&c.value))
+			}
+			("pgp", Some(link)) => write!(content, "KEY:{}", link),
+			// Everything else is assumed to be a social network profile (RFC 9554)
+			(type_, Some(link)) => {
+				write!(
+					content,
+					"SOCIALPROFILE;SERVICE-TYPE={}:{}",
+					param(type_),
+					link
+				)
+			}
+			(type_, None) => write!(
+				content,
+				"SOCIALPROFILE;SERVICE-TYPE={};VALUE=text:{}",
+				param(type_),
+				escape(&c.value)
+			),
+		}
+		.unwrap();
+		line(&mut out, &content);
+	}
+	if let Some(url) = r.resume_url {
+		line(&mut out, &format!("URL:{}", url));
+	}
+	line(&mut out, "END:VCARD");
+	out
+}